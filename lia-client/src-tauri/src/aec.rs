@@ -0,0 +1,611 @@
+// lia-client/src-tauri/src/aec.rs
+// Cancelacion de eco acustico (AEC). El reproductor deja en `EchoReference`
+// la senal que manda a los parlantes y el microfono le resta su eco con un
+// filtro adaptativo NLMS, asi se puede seguir escuchando mientras Lia habla.
+// Todo a 16kHz mono, el formato del microfono despues de `dsp`.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+/// Largo del filtro en milisegundos: cubre la latencia de salida y entrada
+/// mas el rebote en la habitacion.
+const FILTER_MS: usize = 128;
+
+/// Frecuencia de todo el audio de este modulo.
+const SAMPLE_RATE: usize = 16000;
+
+/// Paso de adaptacion del NLMS (0..2). Mas alto converge mas rapido pero
+/// deja mas eco residual.
+const STEP_SIZE: f32 = 0.5;
+
+/// Evita dividir por cero cuando la referencia esta en silencio.
+const REGULARIZATION: f32 = 1e-3;
+
+/// Detector de doble habla (Geigel): si el microfono supera esta fraccion
+/// del pico de la referencia, el usuario esta hablando encima y el filtro
+/// no se adapta (aprenderia su voz como si fuera eco).
+const DOUBLE_TALK_RATIO: f32 = 0.5;
+
+/// Muestras que se mantiene congelada la adaptacion despues de detectar
+/// doble habla (30ms).
+const DOUBLE_TALK_HOLD: usize = SAMPLE_RATE * 30 / 1000;
+
+/// Referencia que se guarda para los lectores atrasados. Lo mas viejo se
+/// descarta.
+const MAX_BACKLOG: usize = SAMPLE_RATE / 10;
+
+/// Atenuacion del eco (ERLE, en veces de energia) a partir de la cual el
+/// filtro se considera convergido: 10 dB.
+const CONVERGED_ERLE: f32 = 10.0;
+
+/// Suavizado de las energias con que se estima el ERLE, por bloque.
+const ERLE_SMOOTHING: f32 = 0.1;
+
+/// Por debajo de este pico la referencia es silencio: no hay eco que quitar.
+const SILENT_REFERENCE: f32 = 1e-3;
+
+/// Si el microfono queda por debajo de la referencia en esta proporcion de
+/// energia (30 dB), no hay camino de eco: Lia suena por auriculares.
+const NO_ECHO_RATIO: f32 = 1000.0;
+
+/// Senal que se esta reproduciendo, compartida entre el reproductor (que
+/// escribe) y los microfonos (que leen). Cada microfono lee con su propio
+/// `ReferenceReader`, asi manos libres y barge-in ven la misma senal.
+#[derive(Default)]
+pub struct EchoReference {
+    buffer: Mutex<ReferenceBuffer>,
+    /// Ultimo camino de eco convergido. Un cancelador nuevo arranca desde
+    /// aca en vez de desde cero: la habitacion no cambia entre respuestas.
+    echo_path: Mutex<EchoPath>,
+}
+
+#[derive(Default)]
+struct EchoPath {
+    weights: Option<Vec<f32>>,
+    /// Cambia con cada salida de audio nueva: lo aprendido con otra salida
+    /// no sirve.
+    output: u64,
+}
+
+#[derive(Default)]
+struct ReferenceBuffer {
+    samples: VecDeque<f32>,
+    /// Posicion absoluta de `samples[0]` desde que arranco el reproductor.
+    start: u64,
+}
+
+impl ReferenceBuffer {
+    fn end(&self) -> u64 {
+        self.start + self.samples.len() as u64
+    }
+}
+
+pub type SharedReference = Arc<EchoReference>;
+
+pub fn create_echo_reference() -> SharedReference {
+    Arc::new(EchoReference::default())
+}
+
+impl EchoReference {
+    /// Agrega audio que acaba de salir hacia los parlantes (16kHz mono).
+    pub fn push(&self, samples: &[f32]) {
+        let Ok(mut buffer) = self.buffer.lock() else {
+            return;
+        };
+        buffer.samples.extend(samples);
+        let excess = buffer.samples.len().saturating_sub(MAX_BACKLOG);
+        buffer.samples.drain(..excess);
+        buffer.start += excess as u64;
+    }
+
+    fn end(&self) -> u64 {
+        self.buffer.lock().map(|buffer| buffer.end()).unwrap_or(0)
+    }
+
+    /// Olvida el camino de eco aprendido: la voz de Lia sale por otra salida
+    /// (de parlantes a auriculares, por ejemplo) y los canceladores abiertos
+    /// vuelven a aprender desde cero.
+    pub fn reset_echo_path(&self) {
+        if let Ok(mut path) = self.echo_path.lock() {
+            path.weights = None;
+            path.output += 1;
+        }
+    }
+
+    fn output(&self) -> u64 {
+        self.echo_path.lock().map(|path| path.output).unwrap_or(0)
+    }
+
+    fn learned_path(&self) -> (u64, Option<Vec<f32>>) {
+        self.echo_path
+            .lock()
+            .map(|path| (path.output, path.weights.clone()))
+            .unwrap_or_default()
+    }
+
+    /// Guarda lo aprendido, si sigue siendo la misma salida.
+    fn store_path(&self, output: u64, weights: &[f32]) {
+        if let Ok(mut path) = self.echo_path.lock() {
+            if path.output == output {
+                path.weights = Some(weights.to_vec());
+            }
+        }
+    }
+}
+
+/// Cursor de un microfono sobre la referencia compartida.
+pub struct ReferenceReader {
+    reference: SharedReference,
+    next: u64,
+}
+
+impl ReferenceReader {
+    /// Empieza desde lo que se reproduce a partir de ahora: lo que quedo de
+    /// antes de abrir el microfono no se alinea con lo que este va a oir.
+    pub fn new(reference: SharedReference) -> Self {
+        let next = reference.end();
+        ReferenceReader { reference, next }
+    }
+
+    /// Las `n` muestras de referencia que corresponden al proximo bloque del
+    /// microfono. Si falta referencia (no se reproduce nada) se completa con
+    /// silencio al principio.
+    pub fn take(&mut self, n: usize) -> Vec<f32> {
+        let mut taken = vec![0.0; n];
+        let Ok(buffer) = self.reference.buffer.lock() else {
+            return taken;
+        };
+        // Si el lector se atraso mas que MAX_BACKLOG, salta a lo mas viejo
+        let from = self.next.max(buffer.start);
+        let available = ((buffer.end() - from) as usize).min(n);
+        let offset = (from - buffer.start) as usize;
+        for (slot, sample) in taken[n - available..]
+            .iter_mut()
+            .zip(buffer.samples.range(offset..offset + available))
+        {
+            *slot = *sample;
+        }
+        self.next = from + available as u64;
+        taken
+    }
+}
+
+/// Filtro adaptativo NLMS que estima el eco a partir de la referencia.
+struct NlmsFilter {
+    weights: Vec<f32>,
+    /// Historia de la referencia duplicada para leer la ventana sin modulo:
+    /// las ultimas `taps` muestras estan en `history[pos..pos + taps]`,
+    /// de la mas nueva a la mas vieja.
+    history: Vec<f32>,
+    pos: usize,
+    /// Energia de la ventana actual, para normalizar el paso.
+    energy: f32,
+}
+
+impl NlmsFilter {
+    fn new(taps: usize) -> Self {
+        NlmsFilter {
+            weights: vec![0.0; taps],
+            history: vec![0.0; 2 * taps],
+            pos: 0,
+            energy: 0.0,
+        }
+    }
+
+    fn taps(&self) -> usize {
+        self.weights.len()
+    }
+
+    /// Agrega una muestra de referencia y retorna el microfono sin el eco.
+    fn process(&mut self, mic: f32, reference: f32, adapt: bool) -> f32 {
+        let taps = self.taps();
+        let oldest = self.history[self.pos + taps - 1];
+        self.pos = if self.pos == 0 {
+            taps - 1
+        } else {
+            self.pos - 1
+        };
+        self.history[self.pos] = reference;
+        self.history[self.pos + taps] = reference;
+        self.energy = (self.energy + reference * reference - oldest * oldest).max(0.0);
+
+        let window = &self.history[self.pos..self.pos + taps];
+        let echo: f32 = self.weights.iter().zip(window).map(|(w, x)| w * x).sum();
+        let error = mic - echo;
+
+        if adapt {
+            let gain = STEP_SIZE * error / (self.energy + REGULARIZATION);
+            for (w, x) in self.weights.iter_mut().zip(window) {
+                *w += gain * x;
+            }
+        }
+        error
+    }
+
+    /// Pico absoluto de la referencia en la ventana del filtro.
+    fn reference_peak(&self) -> f32 {
+        self.history[self.pos..self.pos + self.taps()]
+            .iter()
+            .fold(0.0, |peak, x| x.abs().max(peak))
+    }
+}
+
+/// Quita del microfono el eco de lo que esta reproduciendo Lia.
+pub struct EchoCanceller {
+    filter: NlmsFilter,
+    reference: ReferenceReader,
+    /// Muestras que faltan para volver a adaptar tras doble habla.
+    hold: usize,
+    /// Energias suavizadas del microfono, de la salida y de la referencia
+    /// mientras solo hay eco.
+    mic_energy: f32,
+    output_energy: f32,
+    reference_energy: f32,
+    /// Muestras procesadas con la referencia sonando. Hasta llenar el filtro
+    /// el eco puede no haber llegado todavia al microfono.
+    heard: usize,
+    /// El filtro ya quita el eco (una vez convergido no se vuelve atras).
+    converged: bool,
+    /// El microfono no oye a Lia (auriculares). Tampoco se vuelve atras, pero
+    /// no pasa al proximo cancelador: puede sonar por parlantes.
+    no_echo: bool,
+    /// Salida de audio para la que vale lo aprendido (ver `EchoPath`).
+    output: u64,
+}
+
+impl EchoCanceller {
+    /// Arranca desde el camino de eco que aprendio el ultimo cancelador
+    /// sobre la misma referencia, si lo hay.
+    pub fn new(reference: SharedReference) -> Self {
+        let (output, learned) = reference.learned_path();
+        let mut canceller = EchoCanceller {
+            filter: NlmsFilter::new(SAMPLE_RATE * FILTER_MS / 1000),
+            reference: ReferenceReader::new(reference),
+            hold: 0,
+            mic_energy: 0.0,
+            output_energy: 0.0,
+            reference_energy: 0.0,
+            heard: 0,
+            converged: false,
+            no_echo: false,
+            output,
+        };
+        if let Some(weights) = learned {
+            canceller.filter.weights.copy_from_slice(&weights);
+            canceller.converged = true;
+        }
+        canceller
+    }
+
+    /// Vuelve a aprender desde cero para otra salida de audio.
+    fn restart(&mut self, output: u64) {
+        self.filter = NlmsFilter::new(self.filter.taps());
+        self.hold = 0;
+        self.mic_energy = 0.0;
+        self.output_energy = 0.0;
+        self.reference_energy = 0.0;
+        self.heard = 0;
+        self.converged = false;
+        self.no_echo = false;
+        self.output = output;
+    }
+
+    /// Si la salida ya no trae eco de Lia: el filtro convergio, el microfono
+    /// no oye los parlantes o no se esta reproduciendo nada. Antes de eso el
+    /// eco pasa casi entero y un VAD lo tomaria por el usuario.
+    pub fn settled(&self) -> bool {
+        self.converged || self.no_echo || self.filter.reference_peak() < SILENT_REFERENCE
+    }
+
+    /// Procesa un bloque del microfono (16kHz mono) y lo retorna sin eco.
+    pub fn process(&mut self, mic: &[f32]) -> Vec<f32> {
+        let current = self.reference.reference.output();
+        if current != self.output {
+            self.restart(current);
+        }
+        let reference = self.reference.take(mic.len());
+        // Pico de la referencia en la ventana del filtro mas este bloque
+        let peak = reference
+            .iter()
+            .fold(self.filter.reference_peak(), |peak, x| x.abs().max(peak));
+
+        let mut double_talk = false;
+        let output: Vec<f32> = mic
+            .iter()
+            .zip(&reference)
+            .map(|(&d, &x)| {
+                if d.abs() > DOUBLE_TALK_RATIO * peak {
+                    self.hold = DOUBLE_TALK_HOLD;
+                }
+                let adapt = self.hold == 0;
+                double_talk |= !adapt;
+                self.hold = self.hold.saturating_sub(1);
+                self.filter.process(d, x, adapt)
+            })
+            .collect();
+
+        if peak >= SILENT_REFERENCE {
+            self.heard += mic.len();
+        }
+        // Se mide con el eco ya llegando al microfono y sin el usuario
+        // hablando encima
+        let measure = !double_talk && peak >= SILENT_REFERENCE && !mic.is_empty();
+        if !self.converged && !self.no_echo && measure && self.heard >= self.filter.taps() {
+            let energy = |s: &[f32]| s.iter().map(|x| x * x).sum::<f32>() / s.len() as f32;
+            self.mic_energy += ERLE_SMOOTHING * (energy(mic) - self.mic_energy);
+            self.output_energy += ERLE_SMOOTHING * (energy(&output) - self.output_energy);
+            self.reference_energy += ERLE_SMOOTHING * (energy(&reference) - self.reference_energy);
+            self.converged = self.mic_energy > CONVERGED_ERLE * self.output_energy;
+            self.no_echo = self.mic_energy * NO_ECHO_RATIO < self.reference_energy;
+        }
+        output
+    }
+}
+
+impl Drop for EchoCanceller {
+    /// Deja el camino de eco para el proximo cancelador.
+    fn drop(&mut self) {
+        if self.converged {
+            self.reference
+                .reference
+                .store_path(self.output, &self.filter.weights);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Ruido blanco reproducible (generador congruencial).
+    fn ruido(semilla: u32, n: usize, amplitud: f32) -> Vec<f32> {
+        let mut estado = semilla;
+        (0..n)
+            .map(|_| {
+                estado = estado.wrapping_mul(1664525).wrapping_add(1013904223);
+                ((estado >> 8) as f32 / (1u32 << 24) as f32 * 2.0 - 1.0) * amplitud
+            })
+            .collect()
+    }
+
+    /// "Voz" sintetica: tonos que suben y bajan de volumen.
+    fn voz(n: usize) -> Vec<f32> {
+        (0..n)
+            .map(|i| {
+                let t = i as f32 / SAMPLE_RATE as f32;
+                let envolvente = 0.5 + 0.5 * (2.0 * std::f32::consts::PI * 3.0 * t).sin();
+                let tono = (2.0 * std::f32::consts::PI * 220.0 * t).sin()
+                    + 0.5 * (2.0 * std::f32::consts::PI * 660.0 * t).sin();
+                0.2 * envolvente * tono
+            })
+            .collect()
+    }
+
+    /// Eco de la habitacion: la referencia atenuada, con retardo y dos rebotes.
+    fn eco(referencia: &[f32]) -> Vec<f32> {
+        let respuesta = [(200, 0.25), (260, -0.1), (900, 0.05)];
+        (0..referencia.len())
+            .map(|n| {
+                respuesta
+                    .iter()
+                    .filter(|(retardo, _)| n >= *retardo)
+                    .map(|(retardo, ganancia)| referencia[n - retardo] * ganancia)
+                    .sum()
+            })
+            .collect()
+    }
+
+    fn energia(senal: &[f32]) -> f32 {
+        senal.iter().map(|s| s * s).sum::<f32>() / senal.len() as f32
+    }
+
+    /// Pasa el microfono por el cancelador en bloques de 10ms, con la
+    /// referencia llegando al mismo ritmo que se reproduce.
+    fn cancelar(referencia: &[f32], microfono: &[f32]) -> Vec<f32> {
+        let compartida = create_echo_reference();
+        let mut aec = EchoCanceller::new(compartida.clone());
+        let mut salida = Vec::new();
+        for (r, m) in referencia.chunks(160).zip(microfono.chunks(160)) {
+            compartida.push(r);
+            salida.extend(aec.process(m));
+        }
+        salida
+    }
+
+    #[test]
+    fn test_cancela_el_eco() {
+        let n = 3 * SAMPLE_RATE;
+        let referencia = ruido(7, n, 0.5);
+        let microfono = eco(&referencia);
+
+        let salida = cancelar(&referencia, &microfono);
+        let ultimo = n - SAMPLE_RATE / 2..n;
+        // Atenuacion del eco (ERLE) de mas de 20 dB ya convergido
+        let erle = energia(&microfono[ultimo.clone()]) / energia(&salida[ultimo]);
+        assert!(erle > 100.0, "ERLE {:.1} dB", 10.0 * erle.log10());
+    }
+
+    #[test]
+    fn test_latencia_del_dispositivo() {
+        let n = 3 * SAMPLE_RATE;
+        let latencia = SAMPLE_RATE * 40 / 1000;
+        let referencia = ruido(5, n, 0.5);
+        // El microfono oye el eco 40ms despues de que la muestra paso por
+        // el reproductor (buffers de salida y de entrada)
+        let mut retrasada = vec![0.0; latencia];
+        retrasada.extend(&referencia[..n - latencia]);
+        let microfono = eco(&retrasada);
+
+        let compartida = create_echo_reference();
+        // Sobras de la respuesta anterior, de antes de abrir el microfono
+        compartida.push(&ruido(3, MAX_BACKLOG, 0.5));
+        let mut aec = EchoCanceller::new(compartida.clone());
+
+        // rodio pide audio en rafagas de 20ms, por adelantado
+        let mut salida = Vec::new();
+        for (k, m) in microfono.chunks(160).enumerate() {
+            if k % 2 == 0 {
+                compartida.push(&referencia[k * 160..((k + 2) * 160).min(n)]);
+            }
+            salida.extend(aec.process(m));
+        }
+
+        let ultimo = n - SAMPLE_RATE / 2..n;
+        let erle = energia(&microfono[ultimo.clone()]) / energia(&salida[ultimo]);
+        assert!(erle > 100.0, "ERLE {:.1} dB", 10.0 * erle.log10());
+    }
+
+    #[test]
+    fn test_la_voz_sobrevive_al_eco() {
+        let n = 4 * SAMPLE_RATE;
+        let referencia = ruido(11, n, 0.5);
+        // El usuario empieza a hablar encima de Lia a los 2 segundos
+        let mut habla = vec![0.0; n];
+        habla[2 * SAMPLE_RATE..].copy_from_slice(&voz(n - 2 * SAMPLE_RATE));
+        let microfono: Vec<f32> = eco(&referencia)
+            .iter()
+            .zip(&habla)
+            .map(|(e, v)| e + v)
+            .collect();
+
+        let salida = cancelar(&referencia, &microfono);
+        let tramo = 3 * SAMPLE_RATE..n;
+        let residuo: Vec<f32> = salida[tramo.clone()]
+            .iter()
+            .zip(&habla[tramo.clone()])
+            .map(|(s, v)| s - v)
+            .collect();
+        let eco_original: Vec<f32> = microfono[tramo.clone()]
+            .iter()
+            .zip(&habla[tramo.clone()])
+            .map(|(m, v)| m - v)
+            .collect();
+        // La voz queda casi intacta y el eco sigue muy por debajo
+        assert!(energia(&residuo) < energia(&habla[tramo]) / 10.0);
+        assert!(energia(&residuo) < energia(&eco_original) / 10.0);
+    }
+
+    #[test]
+    fn test_el_camino_de_eco_pasa_al_proximo_cancelador() {
+        let n = 3 * SAMPLE_RATE;
+        let referencia = ruido(17, n, 0.5);
+        let microfono = eco(&referencia);
+        let compartida = create_echo_reference();
+
+        // Primera respuesta: arranca sin entrenar y converge
+        let mut aec = EchoCanceller::new(compartida.clone());
+        assert!(aec.settled());
+        compartida.push(&referencia[..160]);
+        aec.process(&microfono[..160]);
+        assert!(!aec.settled());
+        for (r, m) in referencia.chunks(160).zip(microfono.chunks(160)).skip(1) {
+            compartida.push(r);
+            aec.process(m);
+        }
+        assert!(aec.settled());
+        drop(aec);
+
+        // Segunda respuesta: ya cancela desde el primer bloque
+        let mut aec = EchoCanceller::new(compartida.clone());
+        assert!(aec.settled());
+        let mut salida = Vec::new();
+        for (r, m) in referencia.chunks(160).zip(microfono.chunks(160)).take(10) {
+            compartida.push(r);
+            salida.extend(aec.process(m));
+        }
+        let erle = energia(&microfono[..1600]) / energia(&salida);
+        assert!(erle > CONVERGED_ERLE, "ERLE {:.1} dB", 10.0 * erle.log10());
+
+        // Cambio de salida: lo aprendido no vale, ni para el cancelador
+        // abierto ni para el proximo
+        compartida.reset_echo_path();
+        compartida.push(&referencia[1600..1760]);
+        aec.process(&microfono[1600..1760]);
+        assert!(!aec.settled());
+        drop(aec);
+        let mut aec = EchoCanceller::new(compartida.clone());
+        compartida.push(&referencia[1760..1920]);
+        aec.process(&microfono[1760..1920]);
+        assert!(!aec.settled());
+    }
+
+    #[test]
+    fn test_auriculares_sin_eco() {
+        let n = SAMPLE_RATE;
+        let referencia = ruido(19, n, 0.5);
+        // El microfono solo oye el ruido de la habitacion
+        let microfono = ruido(23, n, 0.002);
+        let compartida = create_echo_reference();
+        let mut aec = EchoCanceller::new(compartida.clone());
+
+        let mut asentado = Vec::new();
+        for (r, m) in referencia.chunks(160).zip(microfono.chunks(160)) {
+            compartida.push(r);
+            aec.process(m);
+            asentado.push(aec.settled());
+        }
+        // Hasta llenar el filtro el eco podria no haber llegado todavia
+        let filtro = FILTER_MS / 10;
+        assert!(asentado[..filtro - 1].iter().all(|a| !a));
+        assert!(asentado[filtro..].iter().all(|&a| a));
+        drop(aec);
+
+        // No se guarda como camino de eco: la proxima respuesta vuelve a medir
+        let mut aec = EchoCanceller::new(compartida.clone());
+        compartida.push(&referencia[..160]);
+        aec.process(&microfono[..160]);
+        assert!(!aec.settled());
+    }
+
+    #[test]
+    fn test_sin_reproduccion_no_toca_el_microfono() {
+        let microfono = voz(SAMPLE_RATE);
+        let mut aec = EchoCanceller::new(create_echo_reference());
+        assert_eq!(aec.process(&microfono), microfono);
+    }
+
+    #[test]
+    fn test_dos_microfonos_sobre_la_misma_referencia() {
+        let n = 3 * SAMPLE_RATE;
+        let referencia = ruido(13, n, 0.5);
+        let microfono = eco(&referencia);
+
+        // Manos libres y barge-in escuchan a la vez
+        let compartida = create_echo_reference();
+        let mut manos_libres = EchoCanceller::new(compartida.clone());
+        let mut barge_in = EchoCanceller::new(compartida.clone());
+        let mut salidas = (Vec::new(), Vec::new());
+        for (r, m) in referencia.chunks(160).zip(microfono.chunks(160)) {
+            compartida.push(r);
+            salidas.0.extend(manos_libres.process(m));
+            salidas.1.extend(barge_in.process(m));
+        }
+
+        let ultimo = n - SAMPLE_RATE / 2..n;
+        for salida in [salidas.0, salidas.1] {
+            let erle = energia(&microfono[ultimo.clone()]) / energia(&salida[ultimo.clone()]);
+            assert!(erle > 100.0, "ERLE {:.1} dB", 10.0 * erle.log10());
+        }
+    }
+
+    #[test]
+    fn test_referencia_acotada() {
+        let referencia = create_echo_reference();
+        let mut lector = ReferenceReader::new(referencia.clone());
+        referencia.push(&vec![1.0; 2 * MAX_BACKLOG]);
+        referencia.push(&[0.5; 10]);
+        // Lo mas viejo se descarto: quedan MAX_BACKLOG muestras
+        let tomadas = lector.take(MAX_BACKLOG);
+        assert_eq!(tomadas[MAX_BACKLOG - 1], 0.5);
+        // Sin referencia pendiente, silencio
+        assert_eq!(lector.take(4), vec![0.0; 4]);
+        referencia.push(&[0.25, 0.75]);
+        assert_eq!(lector.take(4), vec![0.0, 0.0, 0.25, 0.75]);
+
+        // Un lector nuevo no ve lo anterior y no le quita nada al otro
+        let mut nuevo = ReferenceReader::new(referencia.clone());
+        referencia.push(&[0.125]);
+        assert_eq!(nuevo.take(2), vec![0.0, 0.125]);
+        assert_eq!(lector.take(2), vec![0.0, 0.125]);
+    }
+}
@@ -1,123 +1,370 @@
 // lia-client/src-tauri/src/audio.rs
 // Modulo de audio: grabacion real del microfono con echo cancellation.
-// El audio se codifica como WAV (PCM 16-bit, mono, 16kHz) para enviar al backend.
+// El microfono se abre en su formato nativo (cualquier tipo de muestra y
+// numero de canales); aqui se mezcla a mono y se remuestrea para codificar
+// el WAV (PCM 16-bit, mono, 16kHz) que se envia al backend. La grabacion
+// termina cuando el VAD detecta que el usuario dejo de hablar.
 
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{FromSample, Sample, SampleFormat, SizedSample};
+use serde::Deserialize;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::{Arc, Mutex};
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-const SAMPLE_RATE: u32 = 16000;
+use crate::aec::{EchoCanceller, SharedReference};
+use crate::dsp;
+use crate::wakeword::{UtteranceDetector, UtteranceStatus};
+
+/// Frecuencia del audio que se envia al backend.
+pub const SAMPLE_RATE: u32 = 16000;
+
+/// Seccion `[audio]` de config.toml.
+///
+/// ```toml
+/// [audio]
+/// input_device = "USB"
+/// min_duration_ms = 1000
+/// max_duration_ms = 15000
+/// hands_free = true
+/// echo_cancellation = true
+/// barge_in = true
+/// output_device = "Headphones"
+/// volume = 0.8
+/// speed = 1.25
+/// preserve_pitch = true
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields, default)]
+pub struct AudioConfig {
+    /// Microfono por nombre (exacto o parte de el, sin distinguir
+    /// mayusculas). Sin valor se usa el del sistema.
+    pub input_device: Option<String>,
+    /// La grabacion no termina antes de este tiempo aunque haya silencio.
+    pub min_duration_ms: u64,
+    /// Corte de la grabacion aunque el usuario siga hablando (o nunca hable).
+    pub max_duration_ms: u64,
+    /// Escuchar siempre y preguntar a Lia al detectar voz, sin el boton.
+    /// Se puede cambiar desde el HUD.
+    pub hands_free: bool,
+    /// Restar del microfono el eco de la voz de Lia (AEC). Sin AEC el
+    /// microfono se silencia mientras Lia habla.
+    pub echo_cancellation: bool,
+    /// Interrumpir a Lia hablando encima de la respuesta. Necesita
+    /// `echo_cancellation`: sin AEC no se escucha mientras Lia habla.
+    pub barge_in: bool,
+    /// Salida para la voz de Lia, por nombre como `input_device`. Sin valor
+    /// se usa la del sistema. Se puede cambiar desde el HUD.
+    pub output_device: Option<String>,
+    /// Volumen de la voz de 0.0 a 1.0.
+    pub volume: f32,
+    /// Velocidad de la voz: 1.0 normal, de 0.5 a 2.0.
+    pub speed: f32,
+    /// Mantener el tono al cambiar la velocidad. Sin esto la voz se acelera
+    /// como una cinta (mas aguda), pero cuesta menos CPU.
+    pub preserve_pitch: bool,
+}
+
+impl Default for AudioConfig {
+    fn default() -> Self {
+        AudioConfig {
+            input_device: None,
+            min_duration_ms: 1000,
+            max_duration_ms: 15000,
+            hands_free: false,
+            echo_cancellation: true,
+            barge_in: true,
+            output_device: None,
+            volume: 1.0,
+            speed: 1.0,
+            preserve_pitch: true,
+        }
+    }
+}
 
 /// Flag compartido para echo cancellation.
-/// Cuando `is_playing` es true, la grabacion descarta muestras.
+/// Cuando `is_playing` es true y no hay AEC, la grabacion descarta muestras.
 pub type PlayingFlag = Arc<AtomicBool>;
 
 pub fn create_playing_flag() -> PlayingFlag {
     Arc::new(AtomicBool::new(false))
 }
 
-/// Mantiene el estado de una grabacion en curso.
-pub struct AudioRecorder {
-    stream: cpal::Stream,
-    buffer: Arc<Mutex<Vec<f32>>>,
-    sample_rate: u32,
-}
+/// Margen sobre `max_duration_ms` por si el microfono deja de entregar audio
+/// (o todo se descarta porque Lia esta hablando).
+const STALL_MARGIN: Duration = Duration::from_secs(2);
 
 /// Prueba basica del microfono (Fase 1, solo diagnostico).
-pub fn probar_oido() {
+/// Lista los microfonos para que el usuario sepa que nombre poner en `[audio]`.
+pub fn probar_oido(config: &AudioConfig) {
     println!("Iniciando prueba de audicion...");
 
-    let host = cpal::default_host();
-    let device = match host.default_input_device() {
-        Some(d) => d,
-        None => {
+    match input_device_names() {
+        Ok(names) if names.is_empty() => {
             eprintln!("Lia no detecto ningun microfono.");
             return;
         }
-    };
+        Ok(names) => {
+            for (i, name) in names.iter().enumerate() {
+                println!("  Microfono {}: {}", i, name);
+            }
+        }
+        Err(e) => eprintln!("{}", e),
+    }
 
-    println!(
-        "Microfono detectado: {}",
-        device.name().unwrap_or_else(|_| "Desconocido".to_string())
-    );
-    println!("Prueba de audicion terminada exitosamente.");
+    match find_input_device(config) {
+        Ok(device) => {
+            let name = device.name().unwrap_or_else(|_| "Desconocido".to_string());
+            match device.default_input_config() {
+                Ok(format) => println!(
+                    "Microfono elegido: {} ({}Hz, {} canales, {})",
+                    name,
+                    format.sample_rate().0,
+                    format.channels(),
+                    format.sample_format()
+                ),
+                Err(e) => eprintln!("Microfono {} sin formato de entrada: {}", name, e),
+            }
+        }
+        Err(e) => eprintln!("{}", e),
+    }
+    println!("Prueba de audicion terminada.");
 }
 
-/// Inicia la grabacion del microfono con echo cancellation.
-/// Si `playing_flag` es true, las muestras se descartan (Lia esta hablando).
-pub fn start_recording(playing_flag: PlayingFlag) -> Result<AudioRecorder, String> {
-    let host = cpal::default_host();
-    let device = host
-        .default_input_device()
-        .ok_or_else(|| "No se detecto ningun microfono".to_string())?;
+/// Nombres de los microfonos disponibles, en el orden del host.
+pub fn input_device_names() -> Result<Vec<String>, String> {
+    let devices = cpal::default_host()
+        .input_devices()
+        .map_err(|e| format!("Error al enumerar microfonos: {}", e))?;
+    Ok(devices
+        .map(|d| d.name().unwrap_or_else(|_| "Desconocido".to_string()))
+        .collect())
+}
 
-    let config = cpal::StreamConfig {
-        channels: 1,
-        sample_rate: cpal::SampleRate(SAMPLE_RATE),
-        buffer_size: cpal::BufferSize::Default,
+/// Busca el dispositivo pedido (microfono o salida) entre los nombres
+/// disponibles: primero por nombre exacto y si no, como parte del nombre.
+pub fn select_device(names: &[String], wanted: &str) -> Result<usize, String> {
+    let wanted_lower = wanted.to_lowercase();
+    names
+        .iter()
+        .position(|n| n.eq_ignore_ascii_case(wanted))
+        .or_else(|| {
+            names
+                .iter()
+                .position(|n| n.to_lowercase().contains(&wanted_lower))
+        })
+        .ok_or_else(|| {
+            format!(
+                "No existe el dispositivo '{}' (disponibles: {})",
+                wanted,
+                names.join(", ")
+            )
+        })
+}
+
+fn find_input_device(config: &AudioConfig) -> Result<cpal::Device, String> {
+    let host = cpal::default_host();
+    let Some(wanted) = config.input_device.as_deref() else {
+        return host
+            .default_input_device()
+            .ok_or_else(|| "No se detecto ningun microfono".to_string());
     };
 
-    let buffer: Arc<Mutex<Vec<f32>>> = Arc::new(Mutex::new(Vec::new()));
-    let buffer_clone = buffer.clone();
+    let devices: Vec<cpal::Device> = host
+        .input_devices()
+        .map_err(|e| format!("Error al enumerar microfonos: {}", e))?
+        .collect();
+    let names: Vec<String> = devices
+        .iter()
+        .map(|d| d.name().unwrap_or_default())
+        .collect();
+    let index = select_device(&names, wanted)?;
+    Ok(devices.into_iter().nth(index).unwrap())
+}
 
+/// Abre el stream con el tipo de muestra nativo y envia cada bloque en mono.
+fn build_stream<T>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    sender: mpsc::Sender<Vec<f32>>,
+    mute_while_playing: Option<PlayingFlag>,
+) -> Result<cpal::Stream, cpal::BuildStreamError>
+where
+    T: SizedSample,
+    f32: FromSample<T>,
+{
+    let channels = config.channels as usize;
     let err_fn = |err| {
         eprintln!("Error en el stream de audio: {}", err);
     };
 
-    let stream = device
-        .build_input_stream(
-            &config,
-            move |data: &[f32], _: &cpal::InputCallbackInfo| {
-                // Echo cancellation: descartar muestras si Lia esta hablando
-                if playing_flag.load(Ordering::Relaxed) {
-                    return;
-                }
-                if let Ok(mut buf) = buffer_clone.lock() {
-                    buf.extend_from_slice(data);
-                }
-            },
-            err_fn,
-            None,
-        )
+    device.build_input_stream(
+        config,
+        move |data: &[T], _: &cpal::InputCallbackInfo| {
+            // Sin AEC: descartar muestras si Lia esta hablando
+            if mute_while_playing
+                .as_ref()
+                .is_some_and(|flag| flag.load(Ordering::Relaxed))
+            {
+                return;
+            }
+            let mut mono = Vec::new();
+            dsp::downmix_into(data, channels, &mut mono, f32::from_sample);
+            let _ = sender.send(mono);
+        },
+        err_fn,
+        None,
+    )
+}
+
+/// Microfono abierto en su formato nativo que entrega audio a 16kHz mono.
+/// Al soltarlo se cierra el stream.
+pub struct MicInput {
+    _stream: cpal::Stream,
+    receiver: mpsc::Receiver<Vec<f32>>,
+    resampler: dsp::Resampler,
+    echo_canceller: Option<EchoCanceller>,
+}
+
+impl MicInput {
+    /// Abre el microfono de `[audio]` con echo cancellation: con AEC se resta
+    /// el eco de `echo_reference`; sin AEC, si `playing_flag` es true las
+    /// muestras se descartan (Lia esta hablando).
+    pub fn open(
+        config: &AudioConfig,
+        playing_flag: PlayingFlag,
+        echo_reference: &SharedReference,
+    ) -> Result<Self, String> {
+        let device = find_input_device(config)?;
+
+        // Formato nativo: muchos microfonos USB y PipeWire rechazan 16kHz mono
+        let supported = device
+            .default_input_config()
+            .map_err(|e| format!("El microfono no tiene formato de entrada: {}", e))?;
+        let sample_format = supported.sample_format();
+        let stream_config: cpal::StreamConfig = supported.into();
+
+        let (tx, rx) = mpsc::channel();
+        let (pf, echo_canceller) = if config.echo_cancellation {
+            (None, Some(EchoCanceller::new(echo_reference.clone())))
+        } else {
+            (Some(playing_flag), None)
+        };
+
+        let stream = match sample_format {
+            SampleFormat::I8 => build_stream::<i8>(&device, &stream_config, tx, pf),
+            SampleFormat::I16 => build_stream::<i16>(&device, &stream_config, tx, pf),
+            SampleFormat::I32 => build_stream::<i32>(&device, &stream_config, tx, pf),
+            SampleFormat::I64 => build_stream::<i64>(&device, &stream_config, tx, pf),
+            SampleFormat::U8 => build_stream::<u8>(&device, &stream_config, tx, pf),
+            SampleFormat::U16 => build_stream::<u16>(&device, &stream_config, tx, pf),
+            SampleFormat::U32 => build_stream::<u32>(&device, &stream_config, tx, pf),
+            SampleFormat::U64 => build_stream::<u64>(&device, &stream_config, tx, pf),
+            SampleFormat::F32 => build_stream::<f32>(&device, &stream_config, tx, pf),
+            SampleFormat::F64 => build_stream::<f64>(&device, &stream_config, tx, pf),
+            other => return Err(format!("Formato de muestra no soportado: {}", other)),
+        }
         .map_err(|e| format!("No se pudo construir el stream: {}", e))?;
 
-    stream
-        .play()
-        .map_err(|e| format!("No se pudo iniciar la grabacion: {}", e))?;
+        stream
+            .play()
+            .map_err(|e| format!("No se pudo iniciar la grabacion: {}", e))?;
 
-    println!(
-        "Grabacion de audio iniciada ({}Hz, mono, echo cancel activo)",
-        SAMPLE_RATE
-    );
+        println!(
+            "Microfono abierto ({}Hz, {} canales, {}, {})",
+            stream_config.sample_rate.0,
+            stream_config.channels,
+            sample_format,
+            if echo_canceller.is_some() {
+                "AEC activo"
+            } else {
+                "silenciado mientras Lia habla"
+            }
+        );
 
-    Ok(AudioRecorder {
-        stream,
-        buffer,
-        sample_rate: SAMPLE_RATE,
-    })
+        Ok(MicInput {
+            _stream: stream,
+            receiver: rx,
+            resampler: dsp::Resampler::new(stream_config.sample_rate.0, SAMPLE_RATE),
+            echo_canceller,
+        })
+    }
+
+    /// Espera hasta `timeout` el proximo bloque de audio, ya a 16kHz mono y
+    /// sin eco. Vacio si no llego nada (por ejemplo, mientras Lia habla).
+    pub fn read(&mut self, timeout: Duration) -> Result<Vec<f32>, String> {
+        match self.receiver.recv_timeout(timeout) {
+            Ok(chunk) => {
+                let samples = self.resampler.process(&chunk);
+                Ok(match self.echo_canceller.as_mut() {
+                    Some(aec) => aec.process(&samples),
+                    None => samples,
+                })
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => Ok(Vec::new()),
+            Err(mpsc::RecvTimeoutError::Disconnected) => {
+                Err("El microfono dejo de enviar audio".to_string())
+            }
+        }
+    }
+    /// Si lo que entrega `read` ya no trae el eco de Lia (ver
+    /// `EchoCanceller::settled`). Sin AEC el microfono esta silenciado.
+    pub fn echo_settled(&self) -> bool {
+        self.echo_canceller
+            .as_ref()
+            .is_none_or(EchoCanceller::settled)
+    }
 }
 
-/// Detiene la grabacion y retorna los datos como bytes WAV.
-pub fn stop_recording(recorder: AudioRecorder) -> Result<Vec<u8>, String> {
-    drop(recorder.stream);
+/// Graba una frase del microfono con echo cancellation y la retorna como
+/// bytes WAV a 16kHz mono. Bloquea hasta que el VAD detecta el silencio final
+/// o se llega a `max_duration_ms`, y en ese momento llama a `on_end`.
+/// Vacio si el usuario no hablo.
+pub fn record_utterance(
+    config: &AudioConfig,
+    playing_flag: PlayingFlag,
+    echo_reference: &SharedReference,
+    on_end: impl FnOnce(),
+) -> Result<Vec<u8>, String> {
+    let mut mic = MicInput::open(config, playing_flag, echo_reference)?;
+    let mut utterance = UtteranceDetector::new(
+        Arc::new(AtomicBool::new(false)),
+        SAMPLE_RATE,
+        config.min_duration_ms,
+        config.max_duration_ms,
+    );
+    let deadline = Instant::now() + Duration::from_millis(config.max_duration_ms) + STALL_MARGIN;
 
-    let samples = recorder
-        .buffer
-        .lock()
-        .map_err(|e| format!("Error al acceder al buffer: {}", e))?
-        .clone();
+    loop {
+        let chunk = mic.read(Duration::from_millis(100))?;
+        if utterance.push(&chunk) == UtteranceStatus::Done {
+            break;
+        }
+        if Instant::now() >= deadline {
+            eprintln!("El microfono no entrego audio suficiente, cortando la grabacion");
+            break;
+        }
+    }
+    drop(mic);
+    on_end();
 
+    utterance_wav(&utterance.into_samples())
+}
+
+/// WAV de una frase recortada por el VAD. Vacio si no hubo voz.
+pub fn utterance_wav(samples: &[f32]) -> Result<Vec<u8>, String> {
     if samples.is_empty() {
-        return Err("No se grabo ningun audio".to_string());
+        println!("Grabacion finalizada: no se detecto voz");
+        return Ok(Vec::new());
     }
 
     println!(
         "Grabacion finalizada: {} muestras ({:.1}s)",
         samples.len(),
-        samples.len() as f64 / recorder.sample_rate as f64
+        samples.len() as f64 / SAMPLE_RATE as f64
     );
-
-    encode_wav(&samples, recorder.sample_rate)
+    encode_wav(samples, SAMPLE_RATE)
 }
 
 /// Codifica muestras f32 a bytes WAV (PCM 16-bit, mono).
@@ -135,7 +382,7 @@ fn encode_wav(samples: &[f32], sample_rate: u32) -> Result<Vec<u8>, String> {
             .map_err(|e| format!("Error al crear WavWriter: {}", e))?;
 
         for &sample in samples {
-            let clamped = sample.max(-1.0).min(1.0);
+            let clamped = sample.clamp(-1.0, 1.0);
             let value = (clamped * 32767.0) as i16;
             writer
                 .write_sample(value)
@@ -170,4 +417,41 @@ mod tests {
         let wav = result.unwrap();
         assert!(wav.len() > 44);
     }
+
+    #[test]
+    fn test_seleccion_de_microfono() {
+        let names = vec![
+            "default".to_string(),
+            "Blue Yeti USB Microphone".to_string(),
+            "USB Audio Device".to_string(),
+        ];
+        assert_eq!(select_device(&names, "usb audio device"), Ok(2));
+        // Parte del nombre: el primero que coincide
+        assert_eq!(select_device(&names, "USB"), Ok(1));
+        assert_eq!(select_device(&names, "yeti"), Ok(1));
+        assert!(select_device(&names, "Rode")
+            .unwrap_err()
+            .contains("Blue Yeti"));
+    }
+
+    #[test]
+    fn test_seccion_audio() {
+        let config: AudioConfig =
+            toml::from_str("input_device = \"Yeti\"\nmax_duration_ms = 30000").unwrap();
+        assert_eq!(config.input_device.as_deref(), Some("Yeti"));
+        assert_eq!(config.min_duration_ms, 1000);
+        assert_eq!(config.max_duration_ms, 30000);
+        assert!(!config.hands_free);
+        assert!(config.echo_cancellation);
+        assert!(config.barge_in);
+        assert_eq!(config.output_device, None);
+        assert_eq!(config.volume, 1.0);
+        assert_eq!(config.speed, 1.0);
+        assert!(config.preserve_pitch);
+        assert_eq!(
+            toml::from_str::<AudioConfig>("").unwrap(),
+            AudioConfig::default()
+        );
+        assert!(toml::from_str::<AudioConfig>("sample_rate = 16000").is_err());
+    }
 }
@@ -0,0 +1,294 @@
+// lia-client/src-tauri/src/barge_in.rs
+// Barge-in: mientras Lia responde, un thread escucha el microfono (ya sin el
+// eco de la voz de Lia, ver `aec`). Si el usuario empieza a hablar, se avisa
+// para cortar el TTS y el stream, el orquestador vuelve a LISTENING y la
+// frase se graba completa como pregunta de seguimiento.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{oneshot, watch};
+
+use crate::aec::SharedReference;
+use crate::audio::{self, AudioConfig, MicInput, PlayingFlag};
+use crate::orchestrator::Orchestrator;
+use crate::wakeword::{UtteranceDetector, UtteranceStatus};
+
+/// Cada cuanto se revisa si la respuesta termino cuando no llega audio.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Escucha en curso durante una respuesta.
+pub struct BargeIn {
+    /// Se pone en true cuando el usuario interrumpe.
+    interrupted: watch::Receiver<bool>,
+    /// La respuesta termino: dejar de escuchar si nadie interrumpio.
+    done: Arc<AtomicBool>,
+    follow_up: oneshot::Receiver<Option<Vec<u8>>>,
+}
+
+impl BargeIn {
+    /// Empieza a escuchar en un thread propio. El orquestador debe estar en
+    /// RESPONDING.
+    pub fn start(
+        config: AudioConfig,
+        playing_flag: PlayingFlag,
+        echo_reference: SharedReference,
+        orchestrator: Arc<Mutex<Orchestrator>>,
+    ) -> Self {
+        let (interrupted_tx, interrupted) = watch::channel(false);
+        let (follow_up_tx, follow_up) = oneshot::channel();
+        let done = Arc::new(AtomicBool::new(false));
+
+        let done_thread = done.clone();
+        let spawned = std::thread::Builder::new()
+            .name("lia-barge-in".to_string())
+            .spawn(move || {
+                let result =
+                    MicInput::open(&config, playing_flag, &echo_reference).and_then(|mut mic| {
+                        listen(
+                            &config,
+                            |timeout| Ok((mic.read(timeout)?, mic.echo_settled())),
+                            &done_thread,
+                            &interrupted_tx,
+                            &orchestrator,
+                        )
+                    });
+                let wav = result.unwrap_or_else(|e| {
+                    eprintln!("Barge-in: {}", e);
+                    None
+                });
+                let _ = follow_up_tx.send(wav);
+            });
+        if let Err(e) = spawned {
+            eprintln!("No se pudo iniciar el barge-in: {}", e);
+        }
+
+        BargeIn {
+            interrupted,
+            done,
+            follow_up,
+        }
+    }
+
+    /// Se resuelve cuando el usuario interrumpe. Si la escucha termino sin
+    /// interrupcion, nunca se resuelve.
+    pub async fn interrupted(&self) {
+        let mut interrupted = self.interrupted.clone();
+        if interrupted.wait_for(|&v| v).await.is_err() {
+            std::future::pending::<()>().await;
+        }
+    }
+
+    /// Termina la escucha. Si el usuario interrumpio, espera a que termine
+    /// de hablar y retorna su pregunta como WAV.
+    pub async fn finish(self) -> Option<Vec<u8>> {
+        self.done.store(true, Ordering::Relaxed);
+        self.follow_up.await.ok().flatten()
+    }
+}
+
+/// Escucha el audio de `read` hasta que termina la respuesta (`done`) o,
+/// si el usuario empezo a hablar, hasta el final de su frase. `read` dice
+/// ademas si lo que entrega ya no trae eco (ver `EchoCanceller::settled`).
+fn listen(
+    config: &AudioConfig,
+    mut read: impl FnMut(Duration) -> Result<(Vec<f32>, bool), String>,
+    done: &AtomicBool,
+    interrupted: &watch::Sender<bool>,
+    orchestrator: &Mutex<Orchestrator>,
+) -> Result<Option<Vec<u8>>, String> {
+    let mut utterance = UtteranceDetector::hands_free(
+        Arc::new(AtomicBool::new(false)),
+        audio::SAMPLE_RATE,
+        config.min_duration_ms,
+        config.max_duration_ms,
+    );
+    let mut barged_in = false;
+
+    loop {
+        if done.load(Ordering::Relaxed) && !barged_in {
+            return Ok(None);
+        }
+        let (samples, settled) = read(POLL_INTERVAL)?;
+        // Hasta que el AEC converge (o ve que no hay eco, con auriculares) el
+        // microfono todavia trae la voz de Lia: se escucha como silencio para
+        // no interrumpirse sola
+        let status = if settled || barged_in {
+            utterance.push(&samples)
+        } else {
+            utterance.push(&vec![0.0; samples.len()])
+        };
+
+        if status != UtteranceStatus::Waiting && !barged_in {
+            barged_in = true;
+            println!("Barge-in: el usuario interrumpio la respuesta");
+            orchestrator.lock().unwrap().barge_in();
+            let _ = interrupted.send(true);
+        }
+
+        if status == UtteranceStatus::Done {
+            orchestrator.lock().unwrap().start_thinking();
+            return audio::utterance_wav(&utterance.into_samples()).map(Some);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aec::{self, EchoCanceller};
+    use crate::orchestrator::LiaState;
+
+    /// 20ms de voz (o de silencio) a 16kHz, el bloque que entrega `read`.
+    fn bloques(voz: bool, ms: usize) -> Vec<Vec<f32>> {
+        let amplitud = if voz { 0.5 } else { 0.0 };
+        (0..ms / 20)
+            .map(|_| {
+                (0..320)
+                    .map(|i| (i as f32 * 0.1).sin() * amplitud)
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn respondiendo() -> Mutex<Orchestrator> {
+        let mut orc = Orchestrator::new();
+        orc.start_listening();
+        orc.start_thinking();
+        orc.start_responding();
+        Mutex::new(orc)
+    }
+
+    /// Corre `listen`; la respuesta termina cuando se acaba el audio.
+    fn escuchar(
+        audio: Vec<Vec<f32>>,
+        orchestrator: &Mutex<Orchestrator>,
+    ) -> (Option<Vec<u8>>, bool) {
+        let done = AtomicBool::new(false);
+        let (tx, rx) = watch::channel(false);
+        let mut audio = audio.into_iter();
+        let read = |_| {
+            let bloque = audio.next().unwrap_or_else(|| {
+                done.store(true, Ordering::Relaxed);
+                vec![0.0; 320]
+            });
+            Ok((bloque, true))
+        };
+        let wav = listen(&AudioConfig::default(), read, &done, &tx, orchestrator).unwrap();
+        let interrupted = *rx.borrow();
+        (wav, interrupted)
+    }
+
+    #[test]
+    fn test_interrupcion_graba_la_pregunta() {
+        let orchestrator = respondiendo();
+        let mut audio = bloques(false, 1000);
+        audio.extend(bloques(true, 1500));
+
+        // El audio se acaba a mitad de la frase: igual se espera el silencio final
+        let (wav, interrupted) = escuchar(audio, &orchestrator);
+        assert!(interrupted);
+        assert!(wav.unwrap().len() > 44 + 16000 * 2);
+        assert_eq!(orchestrator.lock().unwrap().state(), LiaState::Thinking);
+    }
+
+    /// Voz de Lia: ruido con envolvente de silabas, como sale del TTS.
+    fn voz_de_lia(semilla: u32, n: usize) -> Vec<f32> {
+        let mut estado = semilla;
+        (0..n)
+            .map(|i| {
+                estado = estado.wrapping_mul(1664525).wrapping_add(1013904223);
+                let muestra = (estado >> 8) as f32 / (1u32 << 24) as f32 * 2.0 - 1.0;
+                let t = i as f32 / 16000.0;
+                let silabas = (std::f32::consts::PI * 4.0 * t).sin().abs();
+                0.5 * silabas * muestra
+            })
+            .collect()
+    }
+
+    /// Lo que oye el microfono: el parlante con retardo, atenuado y con un rebote.
+    fn eco(voz: &[f32]) -> Vec<f32> {
+        (0..voz.len())
+            .map(|n| {
+                let directo = if n >= 320 { 0.4 * voz[n - 320] } else { 0.0 };
+                let rebote = if n >= 1100 { -0.1 * voz[n - 1100] } else { 0.0 };
+                directo + rebote
+            })
+            .collect()
+    }
+
+    /// Corre `listen` mientras suena `voz` y el microfono oye `microfono`,
+    /// pasando por el cancelador de eco real.
+    fn responder(
+        referencia: &aec::SharedReference,
+        voz: &[f32],
+        microfono: &[f32],
+        orchestrator: &Mutex<Orchestrator>,
+    ) -> (Option<Vec<u8>>, bool) {
+        let mut aec = EchoCanceller::new(referencia.clone());
+        let done = AtomicBool::new(false);
+        let (tx, rx) = watch::channel(false);
+        let mut pares = voz.chunks(320).zip(microfono.chunks(320));
+        let read = |_| match pares.next() {
+            Some((v, m)) => {
+                referencia.push(v);
+                Ok((aec.process(m), aec.settled()))
+            }
+            None => {
+                done.store(true, Ordering::Relaxed);
+                Ok((vec![0.0; 320], true))
+            }
+        };
+        let wav = listen(&AudioConfig::default(), read, &done, &tx, orchestrator).unwrap();
+        let interrupted = *rx.borrow();
+        (wav, interrupted)
+    }
+
+    /// Una respuesta de Lia por parlantes, sin que el usuario hable.
+    fn responder_sin_hablar(referencia: &aec::SharedReference, semilla: u32) -> bool {
+        let voz = voz_de_lia(semilla, 3 * 16000);
+        let (wav, interrupted) = responder(referencia, &voz, &eco(&voz), &respondiendo());
+        assert!(wav.is_none());
+        interrupted
+    }
+
+    #[test]
+    fn test_el_eco_de_lia_no_interrumpe() {
+        // Sin cancelar, el eco supera el umbral del VAD
+        let eco_crudo = eco(&voz_de_lia(1, 16000));
+        let rms = (eco_crudo.iter().map(|s| s * s).sum::<f32>() / 16000.0).sqrt();
+        assert!(rms > 0.02);
+
+        let referencia = aec::create_echo_reference();
+        // Primera respuesta, con el filtro sin entrenar
+        assert!(!responder_sin_hablar(&referencia, 1));
+        // La siguiente arranca con el camino de eco aprendido
+        assert!(!responder_sin_hablar(&referencia, 2));
+    }
+
+    #[test]
+    fn test_con_auriculares_interrumpe() {
+        // Lia suena por auriculares: el microfono solo oye al usuario, que
+        // empieza a hablar al segundo
+        let orchestrator = respondiendo();
+        let voz = voz_de_lia(4, 4 * 16000);
+        let mut microfono = bloques(false, 1000).concat();
+        microfono.extend(bloques(true, 1500).concat());
+        microfono.resize(voz.len(), 0.0);
+
+        let referencia = aec::create_echo_reference();
+        let (wav, interrupted) = responder(&referencia, &voz, &microfono, &orchestrator);
+        assert!(interrupted);
+        assert!(wav.unwrap().len() > 44 + 16000 * 2);
+        assert_eq!(orchestrator.lock().unwrap().state(), LiaState::Thinking);
+    }
+
+    #[test]
+    fn test_sin_interrupcion() {
+        let orchestrator = respondiendo();
+        let (wav, interrupted) = escuchar(bloques(false, 2000), &orchestrator);
+        assert!(wav.is_none());
+        assert!(!interrupted);
+        assert_eq!(orchestrator.lock().unwrap().state(), LiaState::Responding);
+    }
+}
@@ -9,6 +9,9 @@ use serde::Deserialize;
 use tauri::{AppHandle, Emitter};
 use tokio_tungstenite::{connect_async, tungstenite::Message};
 
+use crate::playback::TtsPlayback;
+use crate::sentinel::RedactionMap;
+
 /// Chunk del Contrato C recibido del Cloud.
 #[derive(Debug, Deserialize)]
 pub struct ContractCChunk {
@@ -20,15 +23,19 @@ pub struct ContractCChunk {
 
 /// Resultado del streaming desde el Cloud.
 pub struct StreamResult {
-    /// Audio TTS acumulado (bytes WAV/MP3 del Cloud, para reproducir despues).
-    pub tts_audio: Vec<Vec<u8>>,
+    /// Chunks de audio TTS que se mandaron a reproducir.
+    pub tts_chunks: usize,
 }
 
 /// Envia Contrato B y retransmite texto al HUD.
-/// Retorna los chunks de audio TTS para reproduccion posterior.
+/// El texto se rehidrata con `redactions` antes de mostrarse, asi los
+/// placeholders de Sentinel vuelven a su valor original solo en local.
+/// Cada chunk de audio TTS va a `tts` apenas llega, sin esperar al texto.
 pub async fn send_to_cloud_and_stream(
     cloud_url: &str,
     contract_b_json: &str,
+    redactions: &RedactionMap,
+    tts: &TtsPlayback,
     app: &AppHandle,
 ) -> Result<StreamResult, String> {
     let (ws_stream, _) = connect_async(cloud_url)
@@ -46,7 +53,8 @@ pub async fn send_to_cloud_and_stream(
 
     println!("Contrato B enviado");
 
-    let mut tts_audio: Vec<Vec<u8>> = Vec::new();
+    let mut tts_chunks = 0;
+    let mut rehydrator = redactions.rehydrator();
 
     while let Some(msg_result) = read.next().await {
         match msg_result {
@@ -55,7 +63,10 @@ pub async fn send_to_cloud_and_stream(
                     "in_progress" => match chunk.chunk_type.as_str() {
                         "code_suggestion" => {
                             if let Some(ref data) = chunk.data {
-                                let _ = app.emit("lia://stream-chunk", data.clone());
+                                let text = rehydrator.push(data);
+                                if !text.is_empty() {
+                                    let _ = app.emit("lia://stream-chunk", text);
+                                }
                             }
                         }
                         "audio" => {
@@ -63,7 +74,8 @@ pub async fn send_to_cloud_and_stream(
                                 match BASE64.decode(data) {
                                     Ok(audio_bytes) => {
                                         println!("TTS audio: {} bytes", audio_bytes.len());
-                                        tts_audio.push(audio_bytes);
+                                        tts.play(audio_bytes);
+                                        tts_chunks += 1;
                                     }
                                     Err(e) => eprintln!("Error base64 audio: {}", e),
                                 }
@@ -96,6 +108,11 @@ pub async fn send_to_cloud_and_stream(
         }
     }
 
+    let rest = rehydrator.finish();
+    if !rest.is_empty() {
+        let _ = app.emit("lia://stream-chunk", rest);
+    }
+
     let _ = write.close().await;
-    Ok(StreamResult { tts_audio })
+    Ok(StreamResult { tts_chunks })
 }
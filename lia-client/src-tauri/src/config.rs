@@ -0,0 +1,102 @@
+// lia-client/src-tauri/src/config.rs
+// Configuracion de usuario de Lia Desktop (~/.lia/config.toml).
+// Cada modulo define su propia seccion; aqui solo se agrupan y se cargan.
+// Las reglas de Sentinel viven aparte en ~/.lia/sentinel.toml.
+
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+use crate::audio::AudioConfig;
+use crate::policy::PrivacyConfig;
+use crate::vision::ScreenConfig;
+
+const CONFIG_FILE: &str = "config.toml";
+
+/// Contenido completo de config.toml. Todas las secciones son opcionales.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LiaConfig {
+    #[serde(default)]
+    pub privacy: PrivacyConfig,
+    #[serde(default)]
+    pub screen: ScreenConfig,
+    #[serde(default)]
+    pub audio: AudioConfig,
+}
+
+/// Carpeta de datos de Lia: `~/.lia`.
+pub fn lia_dir() -> PathBuf {
+    let home = std::env::var("HOME").unwrap_or_else(|_| "/tmp".to_string());
+    PathBuf::from(home).join(".lia")
+}
+
+/// Ruta por defecto de config.toml.
+pub fn default_config_path() -> PathBuf {
+    lia_dir().join(CONFIG_FILE)
+}
+
+impl LiaConfig {
+    /// Parsea el contenido TOML de config.toml.
+    pub fn from_toml(content: &str) -> Result<Self, String> {
+        toml::from_str(content).map_err(|e| format!("{} invalido: {}", CONFIG_FILE, e))
+    }
+
+    /// Carga la configuracion. Si el archivo no existe se usan los valores por defecto.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("No se pudo leer {}: {}", path.display(), e))?;
+        Self::from_toml(&content).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::policy::PolicyAction;
+
+    #[test]
+    fn test_config_vacia() {
+        let config = LiaConfig::from_toml("").unwrap();
+        assert_eq!(config.privacy.default_action, PolicyAction::Redact);
+        assert!(config.privacy.builtin_blocklist);
+        assert!(config.screen.builtin_blocklist);
+    }
+
+    #[test]
+    fn test_seccion_privacy() {
+        let config = LiaConfig::from_toml(
+            r#"
+            [privacy]
+            gitignore_action = "send"
+
+            [[privacy.rules]]
+            pattern = "*.sql"
+            action = "block"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.privacy.gitignore_action, PolicyAction::Send);
+        assert_eq!(config.privacy.rules.len(), 1);
+    }
+
+    #[test]
+    fn test_seccion_screen() {
+        let config = LiaConfig::from_toml(
+            r#"
+            [screen]
+            blocklist = ["Banco"]
+            "#,
+        )
+        .unwrap();
+        assert!(config.screen.builtin_blocklist);
+        assert_eq!(config.screen.blocklist, vec!["Banco".to_string()]);
+    }
+
+    #[test]
+    fn test_seccion_desconocida_es_error() {
+        assert!(LiaConfig::from_toml("[privacidad]\nx = 1").is_err());
+    }
+}
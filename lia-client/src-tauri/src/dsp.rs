@@ -0,0 +1,354 @@
+// lia-client/src-tauri/src/dsp.rs
+// Procesamiento de senal del microfono: mezcla a mono y remuestreo.
+// Los microfonos se abren en su formato nativo (44.1/48 kHz, estereo, i16 o
+// f32...) y aqui se lleva el audio al formato del Contrato B: 16 kHz mono.
+// Tambien el cambio de velocidad de la voz de Lia sin cambiar el tono (WSOLA).
+
+use std::f64::consts::PI;
+
+/// Cruces por cero de la sinc a cada lado. Mas cruces, filtro mas abrupto
+/// y mas costo por muestra.
+const ZERO_CROSSINGS: usize = 16;
+
+/// Fraccion de la frecuencia de Nyquist de salida que se deja pasar. Por
+/// debajo de 1 para que la banda de transicion no deje pasar aliasing.
+const ROLLOFF: f64 = 0.92;
+
+/// Ventana de WSOLA. Tiene que abarcar varios periodos de la voz (el mas
+/// largo, de una voz grave, anda por los 12ms).
+const WSOLA_WINDOW_MS: usize = 30;
+
+/// Cuanto puede correrse cada ventana de WSOLA para empalmar en fase con la
+/// anterior.
+const WSOLA_TOLERANCE_MS: usize = 10;
+
+/// Mezcla muestras intercaladas de `channels` canales a mono promediando
+/// cada frame, y las agrega a `out`.
+pub fn downmix_into<T, F>(data: &[T], channels: usize, out: &mut Vec<f32>, to_f32: F)
+where
+    T: Copy,
+    F: Fn(T) -> f32,
+{
+    let channels = channels.max(1);
+    out.reserve(data.len() / channels);
+    out.extend(
+        data.chunks_exact(channels)
+            .map(|frame| frame.iter().map(|&s| to_f32(s)).sum::<f32>() / channels as f32),
+    );
+}
+
+/// Remuestreador por interpolacion sinc con ventana de Blackman.
+/// Procesa el audio por bloques, asi que sirve igual para una grabacion
+/// completa que para un stream.
+pub struct Resampler {
+    /// Muestras de entrada por muestra de salida.
+    step: f64,
+    /// Frecuencia de corte relativa a la de entrada (1.0 = Nyquist de entrada).
+    cutoff: f64,
+    /// Muestras de entrada a cada lado del punto interpolado.
+    half_width: usize,
+    /// Entrada pendiente, incluida la historia que necesita el filtro.
+    pending: Vec<f32>,
+    /// Posicion en `pending` de la proxima muestra de salida.
+    position: f64,
+}
+
+impl Resampler {
+    pub fn new(from_rate: u32, to_rate: u32) -> Self {
+        let step = from_rate as f64 / to_rate as f64;
+        // Al bajar la frecuencia el filtro corta en el Nyquist de salida
+        let cutoff = (1.0 / step).min(1.0) * ROLLOFF;
+        let half_width = (ZERO_CROSSINGS as f64 / cutoff).ceil() as usize;
+        Resampler {
+            step,
+            cutoff,
+            half_width,
+            // Silencio previo: la primera salida cae sobre la primera entrada
+            pending: vec![0.0; half_width],
+            position: half_width as f64,
+        }
+    }
+
+    fn is_passthrough(&self) -> bool {
+        self.step == 1.0
+    }
+
+    /// Agrega entrada y retorna las muestras de salida que ya se pueden calcular.
+    pub fn process(&mut self, input: &[f32]) -> Vec<f32> {
+        if self.is_passthrough() {
+            return input.to_vec();
+        }
+        self.pending.extend_from_slice(input);
+
+        let mut output = Vec::with_capacity((input.len() as f64 / self.step) as usize + 1);
+        while (self.position.floor() as usize) + self.half_width < self.pending.len() {
+            output.push(self.interpolate(self.position));
+            self.position += self.step;
+        }
+
+        // Descartar la entrada que ya no entra en ninguna ventana
+        let consumed = (self.position.floor() as usize).saturating_sub(self.half_width);
+        self.pending.drain(..consumed.min(self.pending.len()));
+        self.position -= consumed as f64;
+        output
+    }
+
+    /// Completa con silencio y retorna las ultimas muestras de salida.
+    pub fn flush(&mut self) -> Vec<f32> {
+        if self.is_passthrough() {
+            return Vec::new();
+        }
+        // Solo hasta la ultima entrada real: el relleno no debe generar salida
+        let end = self.pending.len() as f64;
+        self.pending
+            .extend(std::iter::repeat_n(0.0, self.half_width + 1));
+        let mut output = Vec::new();
+        while self.position < end {
+            output.push(self.interpolate(self.position));
+            self.position += self.step;
+        }
+        self.pending.clear();
+        output
+    }
+
+    fn interpolate(&self, position: f64) -> f32 {
+        let center = position.floor() as usize;
+        let first = center + 1 - self.half_width;
+        let last = center + self.half_width;
+
+        let mut sum = 0.0;
+        let mut weights = 0.0;
+        for (k, &sample) in self.pending[first..=last].iter().enumerate() {
+            let distance = position - (first + k) as f64;
+            let weight = self.kernel(distance);
+            sum += sample as f64 * weight;
+            weights += weight;
+        }
+        // Normalizar por la suma de pesos mantiene la ganancia en continua
+        (sum / weights) as f32
+    }
+
+    fn kernel(&self, distance: f64) -> f64 {
+        let x = distance * self.cutoff;
+        let sinc = if x.abs() < 1e-9 {
+            1.0
+        } else {
+            (PI * x).sin() / (PI * x)
+        };
+        let u = distance / self.half_width as f64;
+        if u.abs() >= 1.0 {
+            return 0.0;
+        }
+        let window = 0.42 + 0.5 * (PI * u).cos() + 0.08 * (2.0 * PI * u).cos();
+        sinc * window
+    }
+}
+
+/// Cambia la velocidad de una senal intercalada de `channels` canales sin
+/// cambiar el tono (WSOLA): la salida dura `1 / speed` de la entrada. Cada
+/// ventana se toma cerca de donde corresponde en la entrada, corrida lo
+/// necesario para continuar en fase a la anterior, y se solapa con ventana
+/// de Hann al 50%.
+pub fn time_stretch(samples: &[f32], channels: usize, sample_rate: u32, speed: f32) -> Vec<f32> {
+    let channels = channels.max(1);
+    let frames = samples.len() / channels;
+    let window = (sample_rate as usize * WSOLA_WINDOW_MS / 1000).max(2) & !1;
+    if (speed - 1.0).abs() < 1e-3 || speed <= 0.0 || frames < window {
+        return samples.to_vec();
+    }
+
+    let hop = window / 2;
+    let tolerance = sample_rate as usize * WSOLA_TOLERANCE_MS / 1000;
+    // La busqueda del empalme se hace sobre la mezcla a mono
+    let mut mono = Vec::with_capacity(frames);
+    downmix_into(samples, channels, &mut mono, |s| s);
+    let hann: Vec<f32> = (0..window)
+        .map(|n| (0.5 - 0.5 * (2.0 * PI * n as f64 / window as f64).cos()) as f32)
+        .collect();
+
+    let out_frames = (frames as f64 / speed as f64).round() as usize;
+    let mut out = vec![0.0; (out_frames + window) * channels];
+    let mut previous = 0;
+    for (k, out_pos) in (0..out_frames).step_by(hop).enumerate() {
+        let ideal = (out_pos as f64 * speed as f64) as usize;
+        let start = if k == 0 {
+            0
+        } else {
+            best_splice(&mono, previous + hop, ideal, tolerance, hop)
+        };
+        for (n, &weight) in hann.iter().enumerate() {
+            // La primera ventana no tiene con quien solaparse al principio
+            let weight = if k == 0 && n < hop { 1.0 } else { weight };
+            let Some(frame) = samples.get((start + n) * channels..(start + n + 1) * channels)
+            else {
+                break;
+            };
+            let slot = &mut out[(out_pos + n) * channels..(out_pos + n + 1) * channels];
+            for (o, s) in slot.iter_mut().zip(frame) {
+                *o += s * weight;
+            }
+        }
+        previous = start;
+    }
+    out.truncate(out_frames * channels);
+    out
+}
+
+/// Posicion cerca de `ideal` cuyos primeros `len` frames se parecen mas
+/// (correlacion) a `mono[natural..]`, lo que seguiria a la ventana anterior.
+fn best_splice(mono: &[f32], natural: usize, ideal: usize, tolerance: usize, len: usize) -> usize {
+    let last = mono.len().saturating_sub(len);
+    if natural > last {
+        return ideal.min(last);
+    }
+    let target = &mono[natural..natural + len];
+    let correlation = |start: usize| -> f32 {
+        mono[start..start + len]
+            .iter()
+            .zip(target)
+            .map(|(x, y)| x * y)
+            .sum()
+    };
+    (ideal.saturating_sub(tolerance)..=(ideal + tolerance).min(last))
+        .map(|start| (start, correlation(start)))
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(start, _)| start)
+        .unwrap_or(ideal.min(last))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Remuestrea una senal completa de una vez, como referencia para los
+    /// tests. La grabacion usa `Resampler` por bloques.
+    fn resample(samples: &[f32], from_rate: u32, to_rate: u32) -> Vec<f32> {
+        let mut resampler = Resampler::new(from_rate, to_rate);
+        let mut output = resampler.process(samples);
+        output.extend(resampler.flush());
+        output
+    }
+
+    fn seno(freq: f64, rate: u32, segundos: f64) -> Vec<f32> {
+        let n = (rate as f64 * segundos) as usize;
+        (0..n)
+            .map(|i| (2.0 * PI * freq * i as f64 / rate as f64).sin() as f32)
+            .collect()
+    }
+
+    /// Error maximo contra el seno ideal, sin los bordes (ahi el filtro ve silencio).
+    fn error_maximo(salida: &[f32], freq: f64, rate: u32) -> f32 {
+        let ideal = seno(freq, rate, salida.len() as f64 / rate as f64);
+        let borde = rate as usize / 100;
+        salida[borde..salida.len() - borde]
+            .iter()
+            .zip(&ideal[borde..])
+            .map(|(a, b)| (a - b).abs())
+            .fold(0.0, f32::max)
+    }
+
+    fn rms(senal: &[f32]) -> f32 {
+        (senal.iter().map(|s| s * s).sum::<f32>() / senal.len() as f32).sqrt()
+    }
+
+    #[test]
+    fn test_48k_a_16k() {
+        let salida = resample(&seno(440.0, 48000, 1.0), 48000, 16000);
+        assert!((salida.len() as i64 - 16000).abs() <= 1, "{}", salida.len());
+        assert!(error_maximo(&salida, 440.0, 16000) < 0.01);
+    }
+
+    #[test]
+    fn test_44k1_a_16k() {
+        let salida = resample(&seno(1000.0, 44100, 1.0), 44100, 16000);
+        assert!((salida.len() as i64 - 16000).abs() <= 1, "{}", salida.len());
+        assert!(error_maximo(&salida, 1000.0, 16000) < 0.01);
+    }
+
+    #[test]
+    fn test_8k_a_16k() {
+        let salida = resample(&seno(300.0, 8000, 0.5), 8000, 16000);
+        assert!((salida.len() as i64 - 8000).abs() <= 1, "{}", salida.len());
+        assert!(error_maximo(&salida, 300.0, 16000) < 0.01);
+    }
+
+    #[test]
+    fn test_filtra_lo_que_no_cabe_en_16k() {
+        // 12 kHz esta por encima del Nyquist de 16 kHz: no debe reaparecer como alias
+        let salida = resample(&seno(12000.0, 48000, 0.5), 48000, 16000);
+        assert!(rms(&salida[200..salida.len() - 200]) < 0.01);
+    }
+
+    #[test]
+    fn test_por_bloques_igual_que_de_una_vez() {
+        let entrada = seno(440.0, 48000, 0.3);
+        let completa = resample(&entrada, 48000, 16000);
+
+        let mut resampler = Resampler::new(48000, 16000);
+        let mut por_bloques = Vec::new();
+        for bloque in entrada.chunks(441) {
+            por_bloques.extend(resampler.process(bloque));
+        }
+        por_bloques.extend(resampler.flush());
+
+        assert_eq!(por_bloques.len(), completa.len());
+        for (a, b) in por_bloques.iter().zip(&completa) {
+            assert!((a - b).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_misma_frecuencia() {
+        let entrada = seno(440.0, 16000, 0.1);
+        assert_eq!(resample(&entrada, 16000, 16000), entrada);
+    }
+
+    /// Frecuencia dominante contando cruces por cero.
+    fn frecuencia(senal: &[f32], rate: u32) -> f64 {
+        let cruces = senal
+            .windows(2)
+            .filter(|par| (par[0] < 0.0) != (par[1] < 0.0))
+            .count();
+        cruces as f64 / 2.0 / (senal.len() as f64 / rate as f64)
+    }
+
+    #[test]
+    fn test_mas_rapido_sin_cambiar_el_tono() {
+        let entrada = seno(220.0, 24000, 2.0);
+        for speed in [1.5, 0.75] {
+            let salida = time_stretch(&entrada, 1, 24000, speed);
+            let esperado = (entrada.len() as f32 / speed).round() as usize;
+            assert_eq!(salida.len(), esperado);
+            // Mismo tono y mismo volumen, sin los bordes
+            let medio = &salida[2400..salida.len() - 2400];
+            assert!((frecuencia(medio, 24000) - 220.0).abs() < 5.0);
+            assert!((rms(medio) - rms(&entrada)).abs() < 0.05);
+        }
+    }
+
+    #[test]
+    fn test_velocidad_normal_no_toca_el_audio() {
+        let estereo: Vec<f32> = seno(440.0, 16000, 0.2)
+            .into_iter()
+            .flat_map(|s| [s, -s])
+            .collect();
+        assert_eq!(time_stretch(&estereo, 2, 16000, 1.0), estereo);
+        // Estereo: los canales se mueven juntos
+        let salida = time_stretch(&estereo, 2, 16000, 1.25);
+        assert_eq!(salida.len(), 2 * 2560);
+        assert!(salida
+            .chunks(2)
+            .all(|frame| (frame[0] + frame[1]).abs() < 1e-6));
+    }
+
+    #[test]
+    fn test_downmix_estereo_i16() {
+        let estereo: [i16; 6] = [16384, 0, -32768, -32768, 100, 300];
+        let mut mono = Vec::new();
+        downmix_into(&estereo, 2, &mut mono, |s| s as f32 / 32768.0);
+        assert_eq!(mono.len(), 3);
+        assert!((mono[0] - 0.25).abs() < 1e-6);
+        assert!((mono[1] + 1.0).abs() < 1e-6);
+        assert!((mono[2] - 200.0 / 32768.0).abs() < 1e-6);
+    }
+}
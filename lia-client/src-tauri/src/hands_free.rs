@@ -0,0 +1,207 @@
+// lia-client/src-tauri/src/hands_free.rs
+// Modo manos libres: un thread escucha el microfono todo el tiempo, el VAD
+// recorta cada frase y la manda al pipeline de inferencia como si se hubiera
+// pulsado el boton del HUD. Se activa con `[audio] hands_free` o desde el HUD.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::aec::SharedReference;
+use crate::audio::{self, AudioConfig, MicInput, PlayingFlag};
+use crate::orchestrator::{LiaState, Orchestrator};
+use crate::wakeword::{UtteranceDetector, UtteranceStatus};
+
+/// Flag compartido con el HUD: true mientras el modo manos libres esta activo.
+pub type HandsFreeFlag = Arc<AtomicBool>;
+
+/// Cada cuanto se revisa el flag cuando no llega audio.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Espera antes de reintentar si el microfono fallo.
+const RETRY_INTERVAL: Duration = Duration::from_secs(5);
+
+pub fn create_hands_free_flag(enabled: bool) -> HandsFreeFlag {
+    Arc::new(AtomicBool::new(enabled))
+}
+
+/// Lanza el thread de escucha. El microfono solo esta abierto mientras el
+/// modo esta activo. Cada frase se envia por `utterances` como WAV, con el
+/// orquestador ya en THINKING.
+pub fn spawn(
+    config: AudioConfig,
+    enabled: HandsFreeFlag,
+    playing_flag: PlayingFlag,
+    echo_reference: SharedReference,
+    orchestrator: Arc<Mutex<Orchestrator>>,
+    utterances: UnboundedSender<Vec<u8>>,
+) {
+    let spawned = std::thread::Builder::new()
+        .name("lia-manos-libres".to_string())
+        .spawn(move || loop {
+            if !enabled.load(Ordering::Relaxed) {
+                std::thread::sleep(POLL_INTERVAL);
+                continue;
+            }
+            let result = MicInput::open(&config, playing_flag.clone(), &echo_reference).and_then(
+                |mut mic| {
+                    println!("Manos libres: escuchando");
+                    listen(
+                        &config,
+                        |timeout| mic.read(timeout),
+                        &enabled,
+                        &orchestrator,
+                        &utterances,
+                    )
+                },
+            );
+            match result {
+                Ok(()) => println!("Manos libres: desactivado"),
+                Err(e) => {
+                    eprintln!("Manos libres: {}", e);
+                    std::thread::sleep(RETRY_INTERVAL);
+                }
+            }
+        });
+    if let Err(e) = spawned {
+        eprintln!("No se pudo iniciar el modo manos libres: {}", e);
+    }
+}
+
+/// Recorta frases del audio de `read` hasta que el modo se desactiva.
+/// Una frase solo se envia si Lia estaba en IDLE cuando empezo la voz.
+fn listen(
+    config: &AudioConfig,
+    mut read: impl FnMut(Duration) -> Result<Vec<f32>, String>,
+    enabled: &HandsFreeFlag,
+    orchestrator: &Mutex<Orchestrator>,
+    utterances: &UnboundedSender<Vec<u8>>,
+) -> Result<(), String> {
+    let new_detector = || {
+        UtteranceDetector::hands_free(
+            Arc::new(AtomicBool::new(false)),
+            audio::SAMPLE_RATE,
+            config.min_duration_ms,
+            config.max_duration_ms,
+        )
+    };
+    let mut utterance = new_detector();
+    let mut status = UtteranceStatus::Waiting;
+    // La frase en curso paso a Lia a LISTENING
+    let mut owned = false;
+
+    while enabled.load(Ordering::Relaxed) {
+        let chunk = read(POLL_INTERVAL)?;
+        let new_status = utterance.push(&chunk);
+
+        if status == UtteranceStatus::Waiting && new_status != UtteranceStatus::Waiting {
+            let mut orc = orchestrator.lock().unwrap();
+            owned = orc.state() == LiaState::Idle;
+            if owned {
+                orc.start_listening();
+            } else {
+                println!("Manos libres: Lia esta ocupada, se ignora la frase");
+            }
+        }
+        status = new_status;
+
+        if status == UtteranceStatus::Done {
+            let finished = std::mem::replace(&mut utterance, new_detector());
+            status = UtteranceStatus::Waiting;
+            if !std::mem::take(&mut owned) {
+                continue;
+            }
+
+            orchestrator.lock().unwrap().start_thinking();
+            let sent = audio::utterance_wav(&finished.into_samples()).and_then(|wav| {
+                utterances
+                    .send(wav)
+                    .map_err(|_| "el pipeline ya no recibe frases".to_string())
+            });
+            if let Err(e) = sent {
+                orchestrator.lock().unwrap().finish();
+                return Err(e);
+            }
+        }
+    }
+
+    // Desactivado a mitad de una frase: Lia no se queda escuchando
+    if owned {
+        orchestrator.lock().unwrap().finish();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::mpsc;
+
+    /// 20ms de voz (o de silencio) a 16kHz, el bloque que entrega `read`.
+    fn bloques(voz: bool, ms: usize) -> Vec<Vec<f32>> {
+        let amplitud = if voz { 0.5 } else { 0.0 };
+        (0..ms / 20)
+            .map(|_| {
+                (0..320)
+                    .map(|i| (i as f32 * 0.1).sin() * amplitud)
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Corre `listen` sobre el audio dado; el modo se desactiva al agotarlo.
+    fn escuchar(audio: Vec<Vec<f32>>, orchestrator: &Mutex<Orchestrator>) -> Vec<Vec<u8>> {
+        let enabled = create_hands_free_flag(true);
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let mut audio = audio.into_iter();
+        let read = |_| {
+            Ok(audio.next().unwrap_or_else(|| {
+                enabled.store(false, Ordering::Relaxed);
+                Vec::new()
+            }))
+        };
+        listen(&AudioConfig::default(), read, &enabled, orchestrator, &tx).unwrap();
+
+        let mut frases = Vec::new();
+        while let Ok(wav) = rx.try_recv() {
+            frases.push(wav);
+        }
+        frases
+    }
+
+    #[test]
+    fn test_frase_va_al_pipeline() {
+        let orchestrator = Mutex::new(Orchestrator::new());
+        let mut audio = bloques(false, 5000);
+        audio.extend(bloques(true, 2000));
+        audio.extend(bloques(false, 2000));
+
+        let frases = escuchar(audio, &orchestrator);
+        assert_eq!(frases.len(), 1);
+        assert!(frases[0].len() > 44 + 2 * 16000 * 2);
+        assert_eq!(orchestrator.lock().unwrap().state(), LiaState::Thinking);
+    }
+
+    #[test]
+    fn test_ignora_voz_con_lia_ocupada() {
+        let orchestrator = Mutex::new(Orchestrator::new());
+        orchestrator
+            .lock()
+            .unwrap()
+            .transition_to(LiaState::Responding);
+        let mut audio = bloques(true, 2000);
+        audio.extend(bloques(false, 2000));
+
+        assert!(escuchar(audio, &orchestrator).is_empty());
+        assert_eq!(orchestrator.lock().unwrap().state(), LiaState::Responding);
+    }
+
+    #[test]
+    fn test_desactivar_a_mitad_de_frase() {
+        let orchestrator = Mutex::new(Orchestrator::new());
+        let frases = escuchar(bloques(true, 1000), &orchestrator);
+        assert!(frases.is_empty());
+        assert_eq!(orchestrator.lock().unwrap().state(), LiaState::Idle);
+    }
+}
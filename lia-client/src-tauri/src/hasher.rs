@@ -1,10 +1,40 @@
 // lia-client/src-tauri/src/hasher.rs
 // Modulo de hashing: calcula SHA-256 para el sistema de Smart Caching.
 // Permite comparar si el codigo o la imagen han cambiado desde el ultimo envio,
-// evitando retransmitir datos identicos a la nube.
+// evitando retransmitir datos identicos a la nube. Para las capturas tambien
+// hay un hash perceptual (dHash): el reloj o el cursor cambian los bytes pero
+// no la imagen que ve el modelo.
 
+use image::imageops::FilterType;
+use image::DynamicImage;
 use sha2::{Digest, Sha256};
 
+/// Huella de una captura ya enviada al Cloud.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageFingerprint {
+    /// SHA-256 de los bytes codificados (o del layout si se envio en
+    /// mosaicos): clave del cache del Cloud.
+    pub sha256: String,
+    /// dHash de los pixeles.
+    pub dhash: u64,
+    pub width: u32,
+    pub height: u32,
+    /// Hash de cada mosaico. Vacio si se envio la imagen completa.
+    pub tile_hashes: Vec<String>,
+}
+
+impl ImageFingerprint {
+    /// Misma captura byte a byte, o del mismo tamano, partida igual y a lo
+    /// sumo a `max_distance` bits de dHash.
+    pub fn is_similar(&self, other: &ImageFingerprint, max_distance: u32) -> bool {
+        self.sha256 == other.sha256
+            || (self.width == other.width
+                && self.height == other.height
+                && self.tile_hashes.len() == other.tile_hashes.len()
+                && hamming_distance(self.dhash, other.dhash) <= max_distance)
+    }
+}
+
 /// Calcula el SHA-256 de un contenido y lo retorna como string hexadecimal.
 /// Se usa para generar los campos `hash` del Contrato B.
 pub fn compute_sha256(content: &str) -> String {
@@ -22,6 +52,27 @@ pub fn compute_sha256_bytes(data: &[u8]) -> String {
     format!("{:x}", result)
 }
 
+/// Calcula el dHash (difference hash) de 64 bits de una imagen: se reduce a
+/// 9x8 en grises y cada bit indica si un pixel es mas claro que su vecino
+/// de la derecha. Cambios pequenos (un reloj, el cursor) casi no lo alteran.
+pub fn compute_dhash(image: &DynamicImage) -> u64 {
+    let small = image.resize_exact(9, 8, FilterType::Triangle).to_luma8();
+    let mut hash = 0u64;
+    for y in 0..8 {
+        for x in 0..8 {
+            let left = small.get_pixel(x, y).0[0];
+            let right = small.get_pixel(x + 1, y).0[0];
+            hash = (hash << 1) | u64::from(left > right);
+        }
+    }
+    hash
+}
+
+/// Numero de bits distintos entre dos hashes perceptuales.
+pub fn hamming_distance(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -60,4 +111,58 @@ mod tests {
         let hash = compute_sha256_bytes(&data);
         assert_eq!(hash.len(), 64);
     }
+
+    /// "Pantalla" sintetica: bloques de 40x45 con tonos mezclados y un reloj
+    /// de 40x16 en la esquina inferior derecha.
+    fn pantalla(reloj: u8, invertida: bool) -> DynamicImage {
+        let mut image = image::GrayImage::from_fn(640, 360, |x, y| {
+            let base = (((x / 40) * 7 + (y / 45) * 13) % 16 * 16) as u8;
+            image::Luma([if invertida { 255 - base } else { base }])
+        });
+        for y in 340..356 {
+            for x in 596..636 {
+                image.put_pixel(x, y, image::Luma([reloj]));
+            }
+        }
+        DynamicImage::ImageLuma8(image)
+    }
+
+    #[test]
+    fn test_dhash_determinista() {
+        assert_eq!(
+            compute_dhash(&pantalla(0, false)),
+            compute_dhash(&pantalla(0, false))
+        );
+    }
+
+    #[test]
+    fn test_dhash_ignora_cambios_pequenos() {
+        let antes = compute_dhash(&pantalla(0, false));
+        let despues = compute_dhash(&pantalla(255, false));
+        assert!(hamming_distance(antes, despues) <= 2);
+    }
+
+    #[test]
+    fn test_dhash_detecta_cambios_grandes() {
+        let antes = compute_dhash(&pantalla(0, false));
+        let otra = compute_dhash(&pantalla(0, true));
+        assert!(hamming_distance(antes, otra) > 16);
+    }
+
+    #[test]
+    fn test_huella_similar() {
+        let huella = |sha256: &str, dhash: u64, width: u32| ImageFingerprint {
+            sha256: sha256.to_string(),
+            dhash,
+            width,
+            height: 1080,
+            tile_hashes: vec![],
+        };
+        let anterior = huella("a", 0b1011, 1920);
+        assert!(anterior.is_similar(&huella("b", 0b1010, 1920), 2));
+        assert!(!anterior.is_similar(&huella("b", 0b0100, 1920), 2));
+        // Otro tamano (otro modo de captura) nunca es la misma imagen
+        assert!(!anterior.is_similar(&huella("b", 0b1011, 1280), 2));
+        assert!(anterior.is_similar(&huella("a", 0, 1280), 0));
+    }
 }
@@ -1,6 +1,7 @@
 // lia-client/src-tauri/src/orchestrator.rs
 // Maquina de estados global que coordina el flujo completo de una interaccion:
 // IDLE -> LISTENING -> THINKING -> RESPONDING -> IDLE
+// Si el usuario habla encima de la respuesta: RESPONDING -> LISTENING
 
 use serde::Serialize;
 use tauri::{AppHandle, Emitter};
@@ -85,6 +86,13 @@ impl Orchestrator {
         }
     }
 
+    /// Vuelve a LISTENING (el usuario interrumpio la respuesta).
+    pub fn barge_in(&mut self) {
+        if self.state == LiaState::Responding {
+            self.transition_to(LiaState::Listening);
+        }
+    }
+
     /// Vuelve a IDLE (stream completado).
     pub fn finish(&mut self) {
         self.transition_to(LiaState::Idle);
@@ -129,5 +137,23 @@ mod tests {
         // No puede pasar de IDLE a RESPONDING
         orc.start_responding();
         assert_eq!(orc.state(), LiaState::Idle);
+
+        // Solo se interrumpe una respuesta en curso
+        orc.barge_in();
+        assert_eq!(orc.state(), LiaState::Idle);
+    }
+
+    #[test]
+    fn test_interrupcion_y_pregunta_de_seguimiento() {
+        let mut orc = Orchestrator::new();
+        orc.start_listening();
+        orc.start_thinking();
+        orc.start_responding();
+
+        orc.barge_in();
+        assert_eq!(orc.state(), LiaState::Listening);
+
+        orc.start_thinking();
+        assert_eq!(orc.state(), LiaState::Thinking);
     }
 }
@@ -1,12 +1,35 @@
 // lia-client/src-tauri/src/playback.rs
 // Modulo de reproduccion de audio con soporte para echo cancellation.
-// Setea el flag de reproduccion para que el microfono descarte muestras.
+// Setea el flag de reproduccion y copia lo que suena en la referencia del
+// cancelador de eco, para que el microfono pueda restarlo.
+// `TtsPlayback` es el reproductor de larga vida: un thread propio es dueno
+// de la salida de audio y recibe por un canal los fragmentos de TTS (que
+// suenan apenas llegan del Cloud) y los controles del HUD. Salida, volumen y
+// velocidad arrancan con `[audio]` y se cambian desde el HUD.
 
-use rodio::{Decoder, OutputStream, OutputStreamHandle, Sink};
+use cpal::traits::{DeviceTrait, HostTrait};
+use rodio::buffer::SamplesBuffer;
+use rodio::{Decoder, OutputStream, OutputStreamHandle, Sink, Source};
+use serde::{Deserialize, Serialize};
 use std::io::Cursor;
-use std::sync::atomic::Ordering;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::oneshot;
 
-use crate::audio::PlayingFlag;
+use crate::aec::SharedReference;
+use crate::audio::{self, AudioConfig, PlayingFlag};
+use crate::config;
+use crate::dsp;
+
+/// Velocidades de la voz que se aceptan. Fuera de esto no se entiende.
+const MIN_SPEED: f32 = 0.5;
+const MAX_SPEED: f32 = 2.0;
+
+/// Ajustes elegidos en el HUD, en `~/.lia`.
+const SETTINGS_FILE: &str = "playback.toml";
 
 /// Reproductor de audio que mantiene un output stream abierto.
 pub struct AudioPlayer {
@@ -14,13 +37,22 @@ pub struct AudioPlayer {
     _handle: OutputStreamHandle,
     sink: Sink,
     playing_flag: PlayingFlag,
+    echo_reference: SharedReference,
 }
 
 impl AudioPlayer {
-    /// Crea un nuevo reproductor con echo cancellation integrado.
-    pub fn new(playing_flag: PlayingFlag) -> Result<Self, String> {
-        let (stream, handle) = OutputStream::try_default()
-            .map_err(|e| format!("No se pudo abrir el dispositivo de audio: {}", e))?;
+    /// Crea un nuevo reproductor con echo cancellation integrado. `device` es
+    /// el nombre (o parte) de la salida; None usa la del sistema.
+    pub fn new(
+        playing_flag: PlayingFlag,
+        echo_reference: SharedReference,
+        device: Option<&str>,
+    ) -> Result<Self, String> {
+        let (stream, handle) = match device {
+            None => OutputStream::try_default(),
+            Some(wanted) => OutputStream::try_from_device(&find_output_device(wanted)?),
+        }
+        .map_err(|e| format!("No se pudo abrir el dispositivo de audio: {}", e))?;
 
         let sink =
             Sink::try_new(&handle).map_err(|e| format!("No se pudo crear el sink: {}", e))?;
@@ -32,33 +64,652 @@ impl AudioPlayer {
             _handle: handle,
             sink,
             playing_flag,
+            echo_reference,
         })
     }
 
-    /// Reproduce un fragmento de audio (bytes WAV).
-    pub fn play_chunk(&self, audio_bytes: &[u8]) -> Result<(), String> {
+    /// Reproduce un fragmento de audio (bytes WAV) a la velocidad `speed`.
+    /// Con `preserve_pitch` el fragmento se decodifica entero y se estira con
+    /// `dsp::time_stretch`; si no, se acelera con `Source::speed`.
+    pub fn play_chunk(
+        &self,
+        audio_bytes: &[u8],
+        speed: f32,
+        preserve_pitch: bool,
+    ) -> Result<(), String> {
         // Activar echo cancellation
         self.playing_flag.store(true, Ordering::Relaxed);
 
         let cursor = Cursor::new(audio_bytes.to_vec());
-        let source =
-            Decoder::new(cursor).map_err(|e| format!("Error al decodificar audio: {}", e))?;
+        let source = Decoder::new(cursor)
+            .map_err(|e| format!("Error al decodificar audio: {}", e))?
+            .convert_samples::<f32>();
 
-        self.sink.append(source);
+        let source: Box<dyn Source<Item = f32> + Send> = if speed == 1.0 {
+            Box::new(source)
+        } else if preserve_pitch {
+            let channels = source.channels();
+            let sample_rate = source.sample_rate();
+            let samples: Vec<f32> = source.collect();
+            let stretched = dsp::time_stretch(&samples, channels as usize, sample_rate, speed);
+            Box::new(SamplesBuffer::new(channels, sample_rate, stretched))
+        } else {
+            Box::new(source.speed(speed))
+        };
+
+        // La referencia del AEC va despues del cambio de velocidad: es lo que suena
+        self.sink
+            .append(ReferenceTap::new(source, self.echo_reference.clone()));
         Ok(())
     }
 
     /// Detiene toda la reproduccion inmediatamente.
     pub fn stop(&self) {
         self.sink.stop();
+        // Un sink pausado no haria sonar lo que se encole despues
+        self.sink.play();
+        self.playing_flag.store(false, Ordering::Relaxed);
+    }
+
+    pub fn pause(&self) {
+        self.sink.pause();
         self.playing_flag.store(false, Ordering::Relaxed);
     }
 
+    pub fn resume(&self) {
+        self.sink.play();
+    }
+
+    /// Volumen de 0.0 (mudo) a 1.0 (el del audio original).
+    pub fn set_volume(&self, volume: f32) {
+        self.sink.set_volume(volume);
+    }
+
     /// Verifica si el reproductor esta actualmente reproduciendo audio.
+    /// En pausa no suena nada, asi que no cuenta.
     pub fn is_playing(&self) -> bool {
-        let playing = !self.sink.empty();
+        let playing = !self.sink.empty() && !self.sink.is_paused();
         // Actualizar el flag en tiempo real
         self.playing_flag.store(playing, Ordering::Relaxed);
         playing
     }
+
+    /// Fragmentos en el sink: el que suena mas los que esperan.
+    pub fn queued_chunks(&self) -> usize {
+        self.sink.len()
+    }
+}
+
+/// Nombres de las salidas de audio, para elegir una desde el HUD o config.
+pub fn output_device_names() -> Result<Vec<String>, String> {
+    let devices = cpal::default_host()
+        .output_devices()
+        .map_err(|e| format!("Error al enumerar salidas de audio: {}", e))?;
+    Ok(devices
+        .map(|d| d.name().unwrap_or_else(|_| "Desconocido".to_string()))
+        .collect())
+}
+
+/// Busca una salida de audio por nombre, como `[audio] input_device`.
+fn find_output_device(wanted: &str) -> Result<cpal::Device, String> {
+    let devices: Vec<cpal::Device> = cpal::default_host()
+        .output_devices()
+        .map_err(|e| format!("Error al enumerar salidas de audio: {}", e))?
+        .collect();
+    let names: Vec<String> = devices
+        .iter()
+        .map(|d| d.name().unwrap_or_default())
+        .collect();
+    let index = audio::select_device(&names, wanted)?;
+    Ok(devices.into_iter().nth(index).unwrap())
+}
+
+/// Cada cuanto el thread del reproductor revisa el sink si no hay comandos.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+enum PlaybackCommand {
+    /// Encolar un fragmento de audio (bytes WAV/MP3).
+    Play(Vec<u8>),
+    Pause,
+    Resume,
+    /// Cortar lo que suena y descartar lo encolado.
+    Stop,
+    SetVolume(f32),
+    /// Cambiar de salida (None = la del sistema). Responde si se pudo abrir.
+    SetDevice(Option<String>, oneshot::Sender<Result<(), String>>),
+}
+
+/// Ajustes de la voz de Lia vigentes, para mostrarlos en el HUD.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlaybackSettings {
+    /// None = la salida del sistema.
+    pub output_device: Option<String>,
+    pub volume: f32,
+    pub speed: f32,
+    pub preserve_pitch: bool,
+}
+
+impl PlaybackSettings {
+    /// Ajustes de `[audio]`. Falla con el valor fuera de rango en el mensaje.
+    pub fn from_config(config: &AudioConfig) -> Result<Self, String> {
+        let settings = PlaybackSettings {
+            output_device: config.output_device.clone(),
+            volume: config.volume,
+            speed: config.speed,
+            preserve_pitch: config.preserve_pitch,
+        };
+        settings.validate().map_err(|e| format!("[audio] {}", e))?;
+        Ok(settings)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if !(0.0..=1.0).contains(&self.volume) {
+            return Err(format!(
+                "volume debe estar entre 0.0 y 1.0 (es {})",
+                self.volume
+            ));
+        }
+        if !(MIN_SPEED..=MAX_SPEED).contains(&self.speed) {
+            return Err(format!(
+                "speed debe estar entre {} y {} (es {})",
+                MIN_SPEED, MAX_SPEED, self.speed
+            ));
+        }
+        Ok(())
+    }
+
+    /// Aplica los ajustes guardados desde el HUD, que pisan a los de
+    /// `[audio]`. Si el archivo no existe quedan los de `[audio]`.
+    pub fn with_saved(mut self, path: &Path) -> Result<Self, String> {
+        if !path.exists() {
+            return Ok(self);
+        }
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("No se pudo leer {}: {}", path.display(), e))?;
+        let saved: SavedSettings =
+            toml::from_str(&content).map_err(|e| format!("{}: {}", path.display(), e))?;
+        self.output_device = saved.output_device;
+        self.volume = saved.volume;
+        self.speed = saved.speed;
+        self.validate()
+            .map_err(|e| format!("{}: {}", path.display(), e))?;
+        Ok(self)
+    }
+
+    fn save(&self, path: &Path) -> Result<(), String> {
+        let saved = SavedSettings {
+            output_device: self.output_device.clone(),
+            volume: self.volume,
+            speed: self.speed,
+        };
+        let content = toml::to_string(&saved).map_err(|e| e.to_string())?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("No se pudo crear {}: {}", dir.display(), e))?;
+        }
+        std::fs::write(path, content)
+            .map_err(|e| format!("No se pudo guardar {}: {}", path.display(), e))
+    }
+}
+
+/// Lo que se cambia desde el HUD, con las mismas claves que `[audio]`. Se
+/// escribe entero: sin `output_device` es la salida del sistema.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SavedSettings {
+    output_device: Option<String>,
+    volume: f32,
+    speed: f32,
+}
+
+/// Ruta de los ajustes del HUD: `~/.lia/playback.toml`.
+pub fn default_settings_path() -> PathBuf {
+    config::lia_dir().join(SETTINGS_FILE)
+}
+
+/// Handle del reproductor de TTS. El `AudioPlayer` no es Send, asi que vive
+/// en su propio thread y se maneja con comandos por un canal.
+#[derive(Clone)]
+pub struct TtsPlayback {
+    commands: Sender<PlaybackCommand>,
+    /// Fragmentos enviados que todavia no terminaron de sonar.
+    queued: Arc<AtomicUsize>,
+    /// En pausa desde el HUD: lo encolado no avanza hasta `resume`.
+    paused: Arc<AtomicBool>,
+    /// Compartidos con el thread, que los lee al abrir la salida y en cada
+    /// fragmento.
+    settings: Arc<Mutex<PlaybackSettings>>,
+    /// Donde se guardan los cambios del HUD para la proxima sesion.
+    settings_path: PathBuf,
+}
+
+impl TtsPlayback {
+    /// Lanza el thread del reproductor con `settings`; los cambios del HUD
+    /// se guardan en `settings_path`. La salida de audio se abre con el
+    /// primer fragmento, no al arrancar.
+    pub fn spawn(
+        settings: PlaybackSettings,
+        settings_path: PathBuf,
+        playing_flag: PlayingFlag,
+        echo_reference: SharedReference,
+    ) -> Self {
+        let (commands, receiver) = mpsc::channel();
+        let queued = Arc::new(AtomicUsize::new(0));
+        let settings = Arc::new(Mutex::new(settings));
+
+        let queued_thread = queued.clone();
+        let settings_thread = settings.clone();
+        let spawned = std::thread::Builder::new()
+            .name("lia-reproductor".to_string())
+            .spawn(move || {
+                run(
+                    receiver,
+                    playing_flag,
+                    echo_reference,
+                    &queued_thread,
+                    &settings_thread,
+                )
+            });
+        if let Err(e) = spawned {
+            eprintln!("No se pudo iniciar el reproductor: {}", e);
+        }
+
+        TtsPlayback {
+            commands,
+            queued,
+            paused: Arc::new(AtomicBool::new(false)),
+            settings,
+            settings_path,
+        }
+    }
+
+    pub fn settings(&self) -> PlaybackSettings {
+        self.settings.lock().unwrap().clone()
+    }
+
+    fn save_settings(&self) {
+        if let Err(e) = self.settings().save(&self.settings_path) {
+            eprintln!("{}", e);
+        }
+    }
+
+    /// Encola un fragmento; empieza a sonar en cuanto termina el anterior.
+    pub fn play(&self, audio_bytes: Vec<u8>) {
+        self.queued.fetch_add(1, Ordering::SeqCst);
+        if self
+            .commands
+            .send(PlaybackCommand::Play(audio_bytes))
+            .is_err()
+        {
+            self.queued.fetch_sub(1, Ordering::SeqCst);
+            eprintln!("TTS playback error: el reproductor no esta corriendo");
+        }
+    }
+
+    /// Corta la reproduccion y descarta los fragmentos pendientes.
+    pub fn stop(&self) {
+        self.paused.store(false, Ordering::SeqCst);
+        let _ = self.commands.send(PlaybackCommand::Stop);
+    }
+
+    /// Pausa lo que suena hasta `resume` o `stop`.
+    pub fn pause(&self) {
+        self.paused.store(true, Ordering::SeqCst);
+        let _ = self.commands.send(PlaybackCommand::Pause);
+    }
+
+    pub fn resume(&self) {
+        self.paused.store(false, Ordering::SeqCst);
+        let _ = self.commands.send(PlaybackCommand::Resume);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::SeqCst)
+    }
+
+    /// Volumen de 0.0 a 1.0; se mantiene al cambiar de salida.
+    pub fn set_volume(&self, volume: f32) {
+        let volume = volume.clamp(0.0, 1.0);
+        self.settings.lock().unwrap().volume = volume;
+        let _ = self.commands.send(PlaybackCommand::SetVolume(volume));
+        self.save_settings();
+    }
+
+    /// Velocidad de la voz (0.5 a 2.0). Vale desde el proximo fragmento.
+    pub fn set_speed(&self, speed: f32) -> f32 {
+        let speed = speed.clamp(MIN_SPEED, MAX_SPEED);
+        self.settings.lock().unwrap().speed = speed;
+        self.save_settings();
+        speed
+    }
+
+    /// Abre otra salida de audio. Lo que estaba sonando se descarta.
+    pub async fn set_device(&self, device: Option<String>) -> Result<(), String> {
+        let (reply, result) = oneshot::channel();
+        self.commands
+            .send(PlaybackCommand::SetDevice(device, reply))
+            .map_err(|_| "el reproductor no esta corriendo".to_string())?;
+        result
+            .await
+            .map_err(|_| "el reproductor no respondio".to_string())??;
+        self.save_settings();
+        Ok(())
+    }
+
+    /// Fragmentos encolados o sonando. Cero cuando termino todo el TTS.
+    pub fn queue_depth(&self) -> usize {
+        self.queued.load(Ordering::SeqCst)
+    }
+
+    /// No queda nada por sonar sin intervencion del usuario: termino todo el
+    /// TTS o esta en pausa.
+    pub fn is_idle(&self) -> bool {
+        self.queue_depth() == 0 || self.is_paused()
+    }
+}
+
+/// Loop del thread del reproductor.
+fn run(
+    receiver: Receiver<PlaybackCommand>,
+    playing_flag: PlayingFlag,
+    echo_reference: SharedReference,
+    queued: &AtomicUsize,
+    settings: &Mutex<PlaybackSettings>,
+) {
+    let mut player: Option<AudioPlayer> = None;
+    // Fragmentos que ya estan en el sink
+    let mut in_sink = 0;
+
+    let open = |device: Option<&str>| {
+        let volume = settings.lock().unwrap().volume;
+        AudioPlayer::new(playing_flag.clone(), echo_reference.clone(), device).inspect(|opened| {
+            opened.set_volume(volume);
+        })
+    };
+
+    loop {
+        match receiver.recv_timeout(POLL_INTERVAL) {
+            Ok(PlaybackCommand::Play(audio_bytes)) => {
+                let current = settings.lock().unwrap().clone();
+                if player.is_none() {
+                    match open(current.output_device.as_deref()) {
+                        Ok(opened) => player = Some(opened),
+                        Err(e) => eprintln!("AudioPlayer error: {}", e),
+                    }
+                }
+                let played = player
+                    .as_ref()
+                    .map(|p| p.play_chunk(&audio_bytes, current.speed, current.preserve_pitch));
+                match played {
+                    Some(Ok(())) => in_sink += 1,
+                    Some(Err(e)) => {
+                        eprintln!("TTS playback error: {}", e);
+                        queued.fetch_sub(1, Ordering::SeqCst);
+                    }
+                    None => {
+                        queued.fetch_sub(1, Ordering::SeqCst);
+                    }
+                }
+            }
+            Ok(PlaybackCommand::Pause) => {
+                if let Some(player) = &player {
+                    player.pause();
+                }
+            }
+            Ok(PlaybackCommand::Resume) => {
+                if let Some(player) = &player {
+                    player.resume();
+                }
+            }
+            Ok(PlaybackCommand::Stop) => {
+                if let Some(player) = &player {
+                    player.stop();
+                }
+            }
+            Ok(PlaybackCommand::SetVolume(volume)) => {
+                if let Some(player) = &player {
+                    player.set_volume(volume);
+                }
+            }
+            Ok(PlaybackCommand::SetDevice(wanted, reply)) => {
+                let result = open(wanted.as_deref()).map(|opened| {
+                    // Soltar el reproductor anterior corta lo que tenia encolado
+                    if let Some(previous) = player.replace(opened) {
+                        previous.stop();
+                    }
+                    queued.fetch_sub(in_sink, Ordering::SeqCst);
+                    in_sink = 0;
+                    // El eco aprendido era el de la salida anterior
+                    echo_reference.reset_echo_path();
+                    println!(
+                        "Salida de audio: {}",
+                        wanted.as_deref().unwrap_or("la del sistema")
+                    );
+                    settings.lock().unwrap().output_device = wanted;
+                });
+                let _ = reply.send(result);
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+
+        if let Some(player) = &player {
+            // Descontar los fragmentos que terminaron de sonar (o se cortaron)
+            let remaining = player.queued_chunks();
+            if remaining < in_sink {
+                queued.fetch_sub(in_sink - remaining, Ordering::SeqCst);
+                in_sink = remaining;
+            }
+            player.is_playing();
+        }
+    }
+}
+
+/// Fuente que copia a la referencia del AEC cada muestra que se reproduce,
+/// en mono a 16kHz. rodio la lee a medida que la placa pide audio, asi que
+/// la referencia avanza al ritmo de los parlantes.
+struct ReferenceTap<S> {
+    source: S,
+    echo_reference: SharedReference,
+    channels: usize,
+    /// Suma del frame en curso y cuantas muestras lleva.
+    frame_sum: f32,
+    frame_len: usize,
+    /// Bloque mono a la frecuencia de la fuente, pendiente de enviar.
+    block: Vec<f32>,
+    block_size: usize,
+    resampler: dsp::Resampler,
+}
+
+impl<S: Source<Item = f32>> ReferenceTap<S> {
+    fn new(source: S, echo_reference: SharedReference) -> Self {
+        let sample_rate = source.sample_rate();
+        // Bloques de 10ms para no tomar el lock por cada muestra
+        let block_size = (sample_rate as usize / 100).max(1);
+        ReferenceTap {
+            channels: source.channels().max(1) as usize,
+            source,
+            echo_reference,
+            frame_sum: 0.0,
+            frame_len: 0,
+            block: Vec::with_capacity(block_size),
+            block_size,
+            resampler: dsp::Resampler::new(sample_rate, audio::SAMPLE_RATE),
+        }
+    }
+
+    fn send_block(&mut self) {
+        let resampled = self.resampler.process(&self.block);
+        self.echo_reference.push(&resampled);
+        self.block.clear();
+    }
+}
+
+impl<S: Source<Item = f32>> Iterator for ReferenceTap<S> {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let Some(sample) = self.source.next() else {
+            if !self.block.is_empty() {
+                self.send_block();
+            }
+            let tail = self.resampler.flush();
+            self.echo_reference.push(&tail);
+            return None;
+        };
+
+        self.frame_sum += sample;
+        self.frame_len += 1;
+        if self.frame_len == self.channels {
+            self.block.push(self.frame_sum / self.channels as f32);
+            self.frame_sum = 0.0;
+            self.frame_len = 0;
+            if self.block.len() == self.block_size {
+                self.send_block();
+            }
+        }
+        Some(sample)
+    }
+}
+
+impl<S: Source<Item = f32>> Source for ReferenceTap<S> {
+    fn current_frame_len(&self) -> Option<usize> {
+        self.source.current_frame_len()
+    }
+
+    fn channels(&self) -> u16 {
+        self.source.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.source.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.source.total_duration()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aec;
+    use rodio::buffer::SamplesBuffer;
+
+    #[test]
+    fn test_referencia_de_lo_que_suena() {
+        // 100ms de estereo a 48kHz: el canal izquierdo a 0.5 y el derecho a 0.1
+        let estereo: Vec<f32> = (0..4800).flat_map(|_| [0.5, 0.1]).collect();
+        let referencia = aec::create_echo_reference();
+        let mut lector = aec::ReferenceReader::new(referencia.clone());
+        let tap = ReferenceTap::new(SamplesBuffer::new(2, 48000, estereo), referencia.clone());
+        assert_eq!(tap.channels(), 2);
+
+        // Lo que va a los parlantes no cambia
+        let reproducido: Vec<f32> = tap.collect();
+        assert_eq!(reproducido.len(), 9600);
+        assert_eq!(&reproducido[..2], &[0.5, 0.1]);
+
+        // La referencia queda en mono a 16kHz
+        let mono = lector.take(1600);
+        assert!(mono[800..1500].iter().all(|s| (s - 0.3).abs() < 1e-3));
+    }
+
+    fn ruta_temporal(nombre: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("lia-{}-{}", nombre, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir.join(SETTINGS_FILE)
+    }
+
+    /// Handle sin thread: los fragmentos quedan encolados para siempre.
+    fn sin_reproductor(settings_path: PathBuf) -> (TtsPlayback, Receiver<PlaybackCommand>) {
+        let (commands, receiver) = mpsc::channel();
+        let tts = TtsPlayback {
+            commands,
+            queued: Arc::new(AtomicUsize::new(0)),
+            paused: Arc::new(AtomicBool::new(false)),
+            settings: Arc::new(Mutex::new(
+                PlaybackSettings::from_config(&AudioConfig::default()).unwrap(),
+            )),
+            settings_path,
+        };
+        (tts, receiver)
+    }
+
+    #[test]
+    fn test_pausa_mientras_se_espera_el_tts() {
+        let (tts, _receiver) = sin_reproductor(ruta_temporal("pausa"));
+        assert!(tts.is_idle());
+        tts.play(vec![0; 44]);
+        assert!(!tts.is_idle());
+
+        // Pausado, la respuesta puede terminar aunque quede audio encolado
+        tts.pause();
+        assert!(tts.is_idle());
+        assert_eq!(tts.queue_depth(), 1);
+        tts.resume();
+        assert!(!tts.is_idle());
+        tts.pause();
+        tts.stop();
+        assert!(!tts.is_paused());
+    }
+
+    #[test]
+    fn test_ajustes_de_la_config() {
+        let config = AudioConfig {
+            output_device: Some("Headphones".to_string()),
+            volume: 0.8,
+            speed: 1.25,
+            ..AudioConfig::default()
+        };
+        let ajustes = PlaybackSettings::from_config(&config).unwrap();
+        assert_eq!(ajustes.output_device.as_deref(), Some("Headphones"));
+        assert_eq!(ajustes.volume, 0.8);
+        assert_eq!(ajustes.speed, 1.25);
+        assert!(ajustes.preserve_pitch);
+    }
+
+    #[test]
+    fn test_ajustes_fuera_de_rango() {
+        let volumen = AudioConfig {
+            volume: 1.5,
+            ..AudioConfig::default()
+        };
+        let error = PlaybackSettings::from_config(&volumen).unwrap_err();
+        assert!(error.contains("[audio] volume"), "{}", error);
+
+        let velocidad = AudioConfig {
+            speed: 4.0,
+            ..AudioConfig::default()
+        };
+        let error = PlaybackSettings::from_config(&velocidad).unwrap_err();
+        assert!(error.contains("[audio] speed"), "{}", error);
+    }
+
+    #[test]
+    fn test_el_hud_guarda_los_ajustes() {
+        let ruta = ruta_temporal("ajustes");
+        let config = AudioConfig {
+            output_device: Some("Headphones".to_string()),
+            ..AudioConfig::default()
+        };
+        let de_config = PlaybackSettings::from_config(&config).unwrap();
+        // Sin archivo quedan los de [audio]
+        assert_eq!(de_config.clone().with_saved(&ruta).unwrap(), de_config);
+
+        let (tts, _receiver) = sin_reproductor(ruta.clone());
+        tts.set_volume(0.4);
+        assert_eq!(tts.set_speed(1.5), 1.5);
+
+        // La proxima sesion arranca con lo del HUD, salida incluida
+        let guardados = de_config.with_saved(&ruta).unwrap();
+        assert_eq!(guardados.volume, 0.4);
+        assert_eq!(guardados.speed, 1.5);
+        assert_eq!(guardados.output_device, None);
+
+        std::fs::write(&ruta, "volume = 3.0\nspeed = 1.0\n").unwrap();
+        assert!(guardados.with_saved(&ruta).is_err());
+        let _ = std::fs::remove_dir_all(ruta.parent().unwrap());
+    }
 }
@@ -0,0 +1,380 @@
+// lia-client/src-tauri/src/policy.rs
+// Politica de privacidad por archivo: decide si el codigo del archivo activo
+// se envia (siempre redactado por Sentinel) o no sale de la maquina.
+// Combina reglas glob de ~/.lia/config.toml, archivos .liaignore/.gitignore
+// del workspace y una lista integrada de archivos sensibles conocidos.
+
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use ignore::gitignore::Gitignore;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Que hacer con el codigo de un archivo.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PolicyAction {
+    /// Enviar aunque otra regla lo bloquearia. Sentinel igual redacta el codigo.
+    Send,
+    /// Enviar redactado por Sentinel (comportamiento por defecto).
+    Redact,
+    /// No enviar codigo ni captura de pantalla.
+    Block,
+}
+
+/// Resultado de evaluar la politica para un archivo.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PolicyDecision {
+    pub action: PolicyAction,
+    /// Explicacion para el HUD. Puede contener rutas locales: no se envia al Cloud.
+    pub reason: String,
+}
+
+/// Archivos que nunca deberian salir de la maquina.
+/// Patrones sin '/' se comparan contra el nombre del archivo en cualquier carpeta.
+const BUILTIN_BLOCKLIST: &[&str] = &[
+    ".env",
+    ".env.*",
+    "*.pem",
+    "*.key",
+    "*.p12",
+    "*.pfx",
+    "*.jks",
+    "*.keystore",
+    "*.kdbx",
+    "id_rsa",
+    "id_rsa.*",
+    "id_dsa",
+    "id_ecdsa",
+    "id_ed25519",
+    "credentials.json",
+    "**/.aws/credentials",
+    "**/.docker/config.json",
+    ".npmrc",
+    ".pypirc",
+    ".netrc",
+    ".pgpass",
+    "secrets.yaml",
+    "secrets.yml",
+];
+
+/// Archivo de exclusiones propio de Lia (sintaxis de .gitignore).
+const LIAIGNORE_FILE: &str = ".liaignore";
+const GITIGNORE_FILE: &str = ".gitignore";
+
+// ---------------------------------------------------------------------------
+// Configuracion ([privacy] en ~/.lia/config.toml)
+// ---------------------------------------------------------------------------
+
+/// Seccion `[privacy]` de config.toml.
+///
+/// ```toml
+/// [privacy]
+/// gitignore_action = "redact"
+///
+/// [[privacy.rules]]
+/// pattern = "docs/**"
+/// action = "send"
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields, default)]
+pub struct PrivacyConfig {
+    /// Accion para archivos que no coinciden con ninguna regla.
+    pub default_action: PolicyAction,
+    /// Accion para archivos ignorados por .gitignore.
+    pub gitignore_action: PolicyAction,
+    /// Bloquear la lista integrada de archivos sensibles (.env, id_rsa...).
+    pub builtin_blocklist: bool,
+    /// Reglas del usuario; la primera que coincide gana.
+    pub rules: Vec<PolicyRuleConfig>,
+}
+
+impl Default for PrivacyConfig {
+    fn default() -> Self {
+        PrivacyConfig {
+            default_action: PolicyAction::Redact,
+            gitignore_action: PolicyAction::Block,
+            builtin_blocklist: true,
+            rules: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PolicyRuleConfig {
+    pub pattern: String,
+    pub action: PolicyAction,
+}
+
+// ---------------------------------------------------------------------------
+// Politica compilada
+// ---------------------------------------------------------------------------
+
+pub struct PrivacyPolicy {
+    config: PrivacyConfig,
+    /// Un GlobSet por regla de usuario, mismo orden que `config.rules`.
+    rules: Vec<GlobSet>,
+    blocklist: GlobSet,
+}
+
+impl PrivacyPolicy {
+    /// Compila las reglas glob. Falla con el patron invalido en el mensaje.
+    pub fn from_config(config: &PrivacyConfig) -> Result<Self, String> {
+        let rules = config
+            .rules
+            .iter()
+            .map(|r| build_globset(&[r.pattern.as_str()]))
+            .collect::<Result<Vec<_>, _>>()?;
+        let blocklist = build_globset(BUILTIN_BLOCKLIST)?;
+
+        Ok(PrivacyPolicy {
+            config: config.clone(),
+            rules,
+            blocklist,
+        })
+    }
+
+    /// Decide que hacer con el archivo activo del editor.
+    ///
+    /// Orden de precedencia: reglas del usuario, `.liaignore`, lista
+    /// integrada, `.gitignore` y por ultimo la accion por defecto.
+    pub fn evaluate(&self, file_path: &str, workspace_name: &str) -> PolicyDecision {
+        let file = Path::new(file_path);
+        let root = find_workspace_root(file, workspace_name);
+        let relative = root
+            .as_deref()
+            .and_then(|r| file.strip_prefix(r).ok())
+            .unwrap_or(file);
+
+        for (rule, globs) in self.config.rules.iter().zip(&self.rules) {
+            if globs.is_match(relative) {
+                return PolicyDecision {
+                    action: rule.action,
+                    reason: format!("regla de config.toml '{}'", rule.pattern),
+                };
+            }
+        }
+
+        if let Some(ref root) = root {
+            if let Some(ignore_file) = ignored_by(root, file, LIAIGNORE_FILE) {
+                return PolicyDecision {
+                    action: PolicyAction::Block,
+                    reason: format!("listado en {}", ignore_file.display()),
+                };
+            }
+        }
+
+        if self.config.builtin_blocklist && self.blocklist.is_match(relative) {
+            return PolicyDecision {
+                action: PolicyAction::Block,
+                reason: "archivo sensible conocido (claves, credenciales o .env)".to_string(),
+            };
+        }
+
+        if let Some(ref root) = root {
+            if let Some(ignore_file) = ignored_by(root, file, GITIGNORE_FILE) {
+                return PolicyDecision {
+                    action: self.config.gitignore_action,
+                    reason: format!("listado en {}", ignore_file.display()),
+                };
+            }
+        }
+
+        PolicyDecision {
+            action: self.config.default_action,
+            reason: "politica por defecto".to_string(),
+        }
+    }
+}
+
+/// Compila patrones estilo gitignore: sin '/' coinciden en cualquier carpeta.
+fn build_globset(patterns: &[&str]) -> Result<GlobSet, String> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let normalized = if pattern.contains('/') {
+            pattern.trim_start_matches('/').to_string()
+        } else {
+            format!("**/{}", pattern)
+        };
+        let glob = Glob::new(&normalized)
+            .map_err(|e| format!("[privacy] patron invalido '{}': {}", pattern, e))?;
+        builder.add(glob);
+    }
+    builder
+        .build()
+        .map_err(|e| format!("[privacy] no se pudieron compilar los patrones: {}", e))
+}
+
+/// VS Code solo envia el nombre del workspace: se busca la carpeta ancestro
+/// con ese nombre y, si no existe, el repositorio git mas cercano.
+pub fn find_workspace_root(file: &Path, workspace_name: &str) -> Option<PathBuf> {
+    let ancestors = || file.ancestors().skip(1);
+    ancestors()
+        .find(|dir| dir.file_name().is_some_and(|n| n == workspace_name))
+        .or_else(|| ancestors().find(|dir| dir.join(".git").exists()))
+        .map(Path::to_path_buf)
+}
+
+/// Revisa los archivos `file_name` desde la raiz del workspace hasta la
+/// carpeta del archivo. Como en git, el archivo mas profundo tiene la
+/// ultima palabra (puede re-incluir con `!patron`).
+/// Retorna el archivo de exclusion que lo ignora, si hay alguno.
+fn ignored_by(root: &Path, file: &Path, file_name: &str) -> Option<PathBuf> {
+    let parent = file.parent()?;
+    let relative_dirs = parent.strip_prefix(root).ok()?;
+
+    let mut dirs = vec![root.to_path_buf()];
+    let mut current = root.to_path_buf();
+    for component in relative_dirs.components() {
+        current.push(component);
+        dirs.push(current.clone());
+    }
+
+    let mut verdict = None;
+    for dir in dirs {
+        let path = dir.join(file_name);
+        if !path.is_file() {
+            continue;
+        }
+        let (matcher, _) = Gitignore::new(&path);
+        let m = matcher.matched_path_or_any_parents(file, false);
+        if m.is_ignore() {
+            verdict = Some(path);
+        } else if m.is_whitelist() {
+            verdict = None;
+        }
+    }
+    verdict
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Crea un workspace temporal con los archivos indicados (ruta, contenido).
+    fn workspace(files: &[(&str, &str)]) -> PathBuf {
+        let root = std::env::temp_dir()
+            .join(format!("lia-policy-{}", uuid::Uuid::new_v4()))
+            .join("mi-proyecto");
+        for (path, content) in files {
+            let full = root.join(path);
+            std::fs::create_dir_all(full.parent().unwrap()).unwrap();
+            std::fs::write(full, content).unwrap();
+        }
+        root
+    }
+
+    fn policy(toml_config: &str) -> PrivacyPolicy {
+        let config: PrivacyConfig = toml::from_str(toml_config).unwrap();
+        PrivacyPolicy::from_config(&config).unwrap()
+    }
+
+    fn eval(p: &PrivacyPolicy, root: &Path, file: &str) -> PolicyDecision {
+        p.evaluate(root.join(file).to_str().unwrap(), "mi-proyecto")
+    }
+
+    #[test]
+    fn test_archivo_normal_se_redacta() {
+        let root = workspace(&[("src/main.rs", "")]);
+        let d = eval(&policy(""), &root, "src/main.rs");
+        assert_eq!(d.action, PolicyAction::Redact);
+    }
+
+    #[test]
+    fn test_lista_integrada_bloquea() {
+        let root = workspace(&[]);
+        let p = policy("");
+        for file in [
+            ".env",
+            "config/.env.production",
+            "keys/id_rsa",
+            "credentials.json",
+            "certs/server.pem",
+        ] {
+            assert_eq!(
+                eval(&p, &root, file).action,
+                PolicyAction::Block,
+                "{}",
+                file
+            );
+        }
+    }
+
+    #[test]
+    fn test_liaignore_bloquea() {
+        let root = workspace(&[(".liaignore", "fixtures/clientes/\n*.sql\n")]);
+        let p = policy("");
+        let d = eval(&p, &root, "fixtures/clientes/datos.json");
+        assert_eq!(d.action, PolicyAction::Block);
+        assert!(d.reason.contains(".liaignore"));
+        assert_eq!(eval(&p, &root, "db/dump.sql").action, PolicyAction::Block);
+        assert_eq!(eval(&p, &root, "src/app.ts").action, PolicyAction::Redact);
+    }
+
+    #[test]
+    fn test_gitignore_usa_su_accion_configurable() {
+        let root = workspace(&[(".gitignore", "local/\n"), ("src/.gitignore", "!local/\n")]);
+        assert_eq!(
+            eval(&policy(""), &root, "local/notas.md").action,
+            PolicyAction::Block
+        );
+        let p = policy(r#"gitignore_action = "redact""#);
+        assert_eq!(
+            eval(&p, &root, "local/notas.md").action,
+            PolicyAction::Redact
+        );
+        // Un .gitignore mas profundo puede re-incluir
+        assert_eq!(
+            eval(&policy(""), &root, "src/local/util.rs").action,
+            PolicyAction::Redact
+        );
+    }
+
+    #[test]
+    fn test_reglas_de_usuario_tienen_prioridad() {
+        let root = workspace(&[]);
+        let p = policy(
+            r#"
+            [[rules]]
+            pattern = ".env.example"
+            action = "redact"
+
+            [[rules]]
+            pattern = "docs/**"
+            action = "send"
+
+            [[rules]]
+            pattern = "*.csv"
+            action = "block"
+            "#,
+        );
+        assert_eq!(eval(&p, &root, ".env.example").action, PolicyAction::Redact);
+        assert_eq!(eval(&p, &root, "docs/guia.md").action, PolicyAction::Send);
+        assert_eq!(
+            eval(&p, &root, "data/users.csv").action,
+            PolicyAction::Block
+        );
+        assert_eq!(eval(&p, &root, ".env").action, PolicyAction::Block);
+    }
+
+    #[test]
+    fn test_patron_invalido() {
+        let config: PrivacyConfig = toml::from_str(
+            r#"
+            [[rules]]
+            pattern = "src/[abc"
+            action = "block"
+            "#,
+        )
+        .unwrap();
+        let err = PrivacyPolicy::from_config(&config).err().unwrap();
+        assert!(err.contains("src/[abc"), "{}", err);
+    }
+
+    #[test]
+    fn test_sin_workspace_usa_ruta_absoluta() {
+        let p = policy("");
+        let d = p.evaluate("/home/lia/.ssh/id_ed25519", "otro");
+        assert_eq!(d.action, PolicyAction::Block);
+    }
+}
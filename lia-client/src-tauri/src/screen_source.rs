@@ -0,0 +1,373 @@
+// lia-client/src-tauri/src/screen_source.rs
+// Fuentes de pantalla: de donde salen los monitores, las ventanas y los
+// pixeles que procesa vision.rs. En produccion es xcap; sin pantalla (CI,
+// demos, reproducir un fallo) se usa una carpeta de capturas guardadas o un
+// generador sintetico. Todo lo demas (recorte, tapado, mosaicos, Smart
+// Caching) es igual para las tres.
+
+use image::{Rgba, RgbaImage};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
+use xcap::{Monitor, Window};
+
+/// Extensiones que carga `ReplaySource`.
+const REPLAY_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "webp"];
+
+/// Monitor en coordenadas globales de pantalla.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MonitorInfo {
+    pub name: String,
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Ventana de escritorio en coordenadas globales de pantalla.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WindowInfo {
+    pub app_name: String,
+    pub title: String,
+    pub pid: u32,
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub is_minimized: bool,
+    pub is_focused: bool,
+}
+
+/// Origen de las capturas. Los indices de `capture_monitor` son los de
+/// `monitors()`.
+pub trait ScreenSource: Send + Sync {
+    fn monitors(&self) -> Result<Vec<MonitorInfo>, String>;
+    fn windows(&self) -> Result<Vec<WindowInfo>, String>;
+    fn capture_monitor(&self, index: usize) -> Result<RgbaImage, String>;
+}
+
+/// Seccion `[screen.source]` de config.toml.
+///
+/// ```toml
+/// [screen.source]
+/// kind = "replay"
+/// dir = "/home/yo/capturas"
+/// ```
+#[derive(Debug, Clone, PartialEq, Default, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case", deny_unknown_fields)]
+pub enum SourceConfig {
+    /// La pantalla real.
+    #[default]
+    Xcap,
+    /// Imagenes de una carpeta, en orden alfabetico y en bucle.
+    Replay { dir: PathBuf },
+    /// Escritorio generado: un editor y una ventana de chat.
+    Synthetic {
+        #[serde(default = "default_synthetic_width")]
+        width: u32,
+        #[serde(default = "default_synthetic_height")]
+        height: u32,
+    },
+}
+
+fn default_synthetic_width() -> u32 {
+    1280
+}
+
+fn default_synthetic_height() -> u32 {
+    800
+}
+
+/// Crea la fuente configurada.
+pub fn from_config(config: &SourceConfig) -> Result<Box<dyn ScreenSource>, String> {
+    Ok(match config {
+        SourceConfig::Xcap => Box::new(XcapSource),
+        SourceConfig::Replay { dir } => Box::new(ReplaySource::open(dir)?),
+        SourceConfig::Synthetic { width, height } => {
+            Box::new(SyntheticSource::new(*width, *height))
+        }
+    })
+}
+
+// ---------------------------------------------------------------------------
+// xcap
+// ---------------------------------------------------------------------------
+
+/// Pantalla real via xcap.
+pub struct XcapSource;
+
+impl ScreenSource for XcapSource {
+    fn monitors(&self) -> Result<Vec<MonitorInfo>, String> {
+        let monitors = Monitor::all().map_err(|e| format!("Error al enumerar monitores: {}", e))?;
+        Ok(monitors
+            .iter()
+            .map(|m| MonitorInfo {
+                name: m.name().to_string(),
+                x: m.x(),
+                y: m.y(),
+                width: m.width(),
+                height: m.height(),
+            })
+            .collect())
+    }
+
+    fn windows(&self) -> Result<Vec<WindowInfo>, String> {
+        let windows = Window::all().map_err(|e| format!("Error al enumerar ventanas: {}", e))?;
+        Ok(windows
+            .iter()
+            .map(|w| WindowInfo {
+                app_name: w.app_name().to_string(),
+                title: w.title().to_string(),
+                pid: w.pid(),
+                x: w.x(),
+                y: w.y(),
+                width: w.width(),
+                height: w.height(),
+                is_minimized: w.is_minimized(),
+                is_focused: w.is_focused(),
+            })
+            .collect())
+    }
+
+    fn capture_monitor(&self, index: usize) -> Result<RgbaImage, String> {
+        let monitors = Monitor::all().map_err(|e| format!("Error al enumerar monitores: {}", e))?;
+        monitors
+            .get(index)
+            .ok_or_else(|| format!("El monitor {} ya no existe", index))?
+            .capture_image()
+            .map_err(|e| format!("Error al capturar pantalla: {}", e))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Carpeta de capturas
+// ---------------------------------------------------------------------------
+
+/// Reproduce las imagenes de una carpeta como si fueran un unico monitor.
+/// Cada captura avanza a la siguiente; al llegar al final vuelve a empezar.
+/// No hay ventanas: las capturas guardadas ya deberian estar tapadas.
+pub struct ReplaySource {
+    frames: Vec<PathBuf>,
+    next: Mutex<usize>,
+}
+
+impl ReplaySource {
+    pub fn open(dir: &Path) -> Result<Self, String> {
+        let entries = std::fs::read_dir(dir)
+            .map_err(|e| format!("No se pudo leer {}: {}", dir.display(), e))?;
+        let mut frames: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| {
+                path.extension()
+                    .and_then(|ext| ext.to_str())
+                    .is_some_and(|ext| REPLAY_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+            })
+            .collect();
+        if frames.is_empty() {
+            return Err(format!("{} no contiene imagenes", dir.display()));
+        }
+        frames.sort();
+        Ok(ReplaySource {
+            frames,
+            next: Mutex::new(0),
+        })
+    }
+
+    fn current(&self) -> &Path {
+        &self.frames[*self.next.lock().unwrap()]
+    }
+}
+
+impl ScreenSource for ReplaySource {
+    fn monitors(&self) -> Result<Vec<MonitorInfo>, String> {
+        // El "monitor" mide lo que la imagen que se va a capturar
+        let path = self.current();
+        let (width, height) = image::image_dimensions(path)
+            .map_err(|e| format!("No se pudo leer {}: {}", path.display(), e))?;
+        Ok(vec![MonitorInfo {
+            name: "replay".to_string(),
+            x: 0,
+            y: 0,
+            width,
+            height,
+        }])
+    }
+
+    fn windows(&self) -> Result<Vec<WindowInfo>, String> {
+        Ok(Vec::new())
+    }
+
+    fn capture_monitor(&self, index: usize) -> Result<RgbaImage, String> {
+        if index != 0 {
+            return Err(format!("No existe el monitor {} (hay 1)", index));
+        }
+        let mut next = self.next.lock().unwrap();
+        let path = &self.frames[*next];
+        let image =
+            image::open(path).map_err(|e| format!("No se pudo leer {}: {}", path.display(), e))?;
+        *next = (*next + 1) % self.frames.len();
+        Ok(image.to_rgba8())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Escritorio sintetico
+// ---------------------------------------------------------------------------
+
+const EDITOR_BACKGROUND: Rgba<u8> = Rgba([250, 250, 250, 255]);
+const EDITOR_TEXT: Rgba<u8> = Rgba([40, 40, 40, 255]);
+const CHAT_BACKGROUND: Rgba<u8> = Rgba([74, 21, 75, 255]);
+const LINE_HEIGHT: u32 = 20;
+const CURSOR_SIZE: (u32, u32) = (8, 16);
+
+/// Escritorio generado de un solo monitor: un editor enfocado en los dos
+/// tercios izquierdos y un chat (que la blocklist integrada tapa) arriba a
+/// la derecha. Cada captura mueve el cursor del editor, asi dos capturas
+/// seguidas son casi iguales pero no identicas.
+pub struct SyntheticSource {
+    width: u32,
+    height: u32,
+    windows: Vec<WindowInfo>,
+    frame: AtomicU32,
+}
+
+impl SyntheticSource {
+    pub fn new(width: u32, height: u32) -> Self {
+        let window = |app_name: &str, title: &str, x: u32, width: u32, height: u32| WindowInfo {
+            app_name: app_name.to_string(),
+            title: title.to_string(),
+            pid: 0,
+            x: x as i32,
+            y: 0,
+            width,
+            height,
+            is_minimized: false,
+            is_focused: app_name == "Code",
+        };
+        let editor_width = width * 2 / 3;
+        SyntheticSource {
+            width,
+            height,
+            windows: vec![
+                window("Code", "main.rs - lia", 0, editor_width, height),
+                window(
+                    "Slack",
+                    "#general",
+                    editor_width,
+                    width - editor_width,
+                    height / 2,
+                ),
+            ],
+            frame: AtomicU32::new(0),
+        }
+    }
+
+    /// Pixel del fondo: lineas de "codigo" de largo variable en el editor
+    /// y el color del chat en su ventana.
+    fn background(&self, x: u32, y: u32) -> Rgba<u8> {
+        let chat = &self.windows[1];
+        if x >= chat.x as u32 && y < chat.height {
+            return CHAT_BACKGROUND;
+        }
+        let line = y / LINE_HEIGHT;
+        let indent = 16 + (line % 4) * 24;
+        let length = 80 + (line * 37 % 11) * 30;
+        let in_text = y % LINE_HEIGHT >= 4 && y % LINE_HEIGHT < 14;
+        if in_text && x >= indent && x < indent + length {
+            EDITOR_TEXT
+        } else {
+            EDITOR_BACKGROUND
+        }
+    }
+}
+
+impl ScreenSource for SyntheticSource {
+    fn monitors(&self) -> Result<Vec<MonitorInfo>, String> {
+        Ok(vec![MonitorInfo {
+            name: "synthetic".to_string(),
+            x: 0,
+            y: 0,
+            width: self.width,
+            height: self.height,
+        }])
+    }
+
+    fn windows(&self) -> Result<Vec<WindowInfo>, String> {
+        Ok(self.windows.clone())
+    }
+
+    fn capture_monitor(&self, index: usize) -> Result<RgbaImage, String> {
+        if index != 0 {
+            return Err(format!("No existe el monitor {} (hay 1)", index));
+        }
+        let frame = self.frame.fetch_add(1, Ordering::Relaxed);
+        let mut image = RgbaImage::from_fn(self.width, self.height, |x, y| self.background(x, y));
+
+        // El cursor avanza un caracter por captura en la tercera linea
+        let columns = (self.windows[0].width / CURSOR_SIZE.0).max(1);
+        let cursor_x = (frame % columns) * CURSOR_SIZE.0;
+        let cursor_y = 2 * LINE_HEIGHT;
+        for y in cursor_y..(cursor_y + CURSOR_SIZE.1).min(self.height) {
+            for x in cursor_x..(cursor_x + CURSOR_SIZE.0).min(self.width) {
+                image.put_pixel(x, y, EDITOR_TEXT);
+            }
+        }
+        Ok(image)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sintetica_cambia_poco_entre_capturas() {
+        let source = SyntheticSource::new(320, 200);
+        let primera = source.capture_monitor(0).unwrap();
+        let segunda = source.capture_monitor(0).unwrap();
+        assert_eq!(primera.dimensions(), (320, 200));
+
+        let distintos = primera
+            .pixels()
+            .zip(segunda.pixels())
+            .filter(|(a, b)| a != b)
+            .count();
+        assert!(distintos > 0 && distintos <= 2 * (CURSOR_SIZE.0 * CURSOR_SIZE.1) as usize);
+        assert!(source.capture_monitor(1).is_err());
+    }
+
+    #[test]
+    fn test_replay_en_orden_y_en_bucle() {
+        let dir = std::env::temp_dir().join(format!("lia-replay-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        RgbaImage::from_pixel(40, 30, Rgba([255, 0, 0, 255]))
+            .save(dir.join("b.png"))
+            .unwrap();
+        RgbaImage::from_pixel(20, 10, Rgba([0, 0, 255, 255]))
+            .save(dir.join("a.png"))
+            .unwrap();
+        std::fs::write(dir.join("notas.txt"), "no es una imagen").unwrap();
+
+        let source = ReplaySource::open(&dir).unwrap();
+        assert_eq!(source.monitors().unwrap()[0].width, 20);
+        assert_eq!(
+            *source.capture_monitor(0).unwrap().get_pixel(0, 0),
+            Rgba([0, 0, 255, 255])
+        );
+        assert_eq!(source.monitors().unwrap()[0].width, 40);
+        assert_eq!(source.capture_monitor(0).unwrap().dimensions(), (40, 30));
+        // Vuelve a la primera
+        assert_eq!(source.capture_monitor(0).unwrap().dimensions(), (20, 10));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_replay_sin_imagenes() {
+        let dir = std::env::temp_dir().join(format!("lia-replay-vacia-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        assert!(ReplaySource::open(&dir).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
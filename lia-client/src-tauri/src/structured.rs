@@ -0,0 +1,542 @@
+// lia-client/src-tauri/src/structured.rs
+// Deteccion de secretos por estructura en archivos de configuracion
+// (JSON, YAML, TOML/INI y .env). En vez de buscar la forma de un secreto,
+// busca pares clave/valor cuya clave es sensible (password, token, ...) y
+// marca el valor, tenga comillas o no.
+//
+// La ventana de codigo puede empezar o terminar a mitad de un objeto, asi que
+// el analisis es tolerante: lo que no se reconoce se ignora y queda para las
+// regex de Sentinel.
+
+use regex::Regex;
+use std::path::Path;
+
+/// Formato de configuracion del contenido.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Json,
+    Yaml,
+    /// TOML, INI y .properties (`clave = valor` con secciones `[tabla]`).
+    Toml,
+    Dotenv,
+}
+
+impl ConfigFormat {
+    /// Deduce el formato del languageId de VS Code o, si no es concluyente,
+    /// del nombre del archivo.
+    pub fn detect(language: &str, file_path: &str) -> Option<Self> {
+        match language {
+            "json" | "jsonc" | "json5" => return Some(ConfigFormat::Json),
+            "yaml" | "dockercompose" => return Some(ConfigFormat::Yaml),
+            "toml" | "ini" | "properties" => return Some(ConfigFormat::Toml),
+            "dotenv" => return Some(ConfigFormat::Dotenv),
+            _ => {}
+        }
+
+        let name = Path::new(file_path)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        if name == ".env" || name.starts_with(".env.") || name.ends_with(".env") {
+            return Some(ConfigFormat::Dotenv);
+        }
+        match name.rsplit_once('.').map(|(_, ext)| ext) {
+            Some("json") => Some(ConfigFormat::Json),
+            Some("yaml") | Some("yml") => Some(ConfigFormat::Yaml),
+            Some("toml") | Some("ini") | Some("cfg") | Some("conf") | Some("properties") => {
+                Some(ConfigFormat::Toml)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Claves sensibles. Cada entrada es una secuencia de palabras: la clave se
+/// parte por `_`, `-`, `.`, espacios y camelCase, y es sensible si contiene
+/// alguna entrada completa (`dbPassword`, `DB_PASS`, `spring.datasource.password`).
+const SENSITIVE_KEYS: &[&str] = &[
+    "password",
+    "passwd",
+    "pwd",
+    "pass",
+    "passphrase",
+    "secret",
+    "secrets",
+    "token",
+    "tokens",
+    "apikey",
+    "api_key",
+    "access_key",
+    "private_key",
+    "signing_key",
+    "encryption_key",
+    "auth_key",
+    "credential",
+    "credentials",
+    "connection_string",
+    "dsn",
+];
+
+/// Detector de valores sensibles en archivos de configuracion.
+pub struct StructuredDetector {
+    /// Secuencias de palabras en minusculas.
+    keys: Vec<Vec<String>>,
+    dotenv_line: Regex,
+    toml_line: Regex,
+    toml_table: Regex,
+    yaml_line: Regex,
+    yaml_item: Regex,
+}
+
+impl StructuredDetector {
+    /// `extra_keys` se suman al diccionario integrado.
+    pub fn new(extra_keys: &[String]) -> Self {
+        let keys = SENSITIVE_KEYS
+            .iter()
+            .copied()
+            .chain(extra_keys.iter().map(String::as_str))
+            .map(key_words)
+            .filter(|words| !words.is_empty())
+            .collect();
+
+        StructuredDetector {
+            keys,
+            dotenv_line: Regex::new(r"^[ \t]*(?:export[ \t]+)?([A-Za-z_][A-Za-z0-9_.\-]*)[ \t]*=(.*)$")
+                .unwrap(),
+            toml_line: Regex::new(
+                r#"^[ \t]*("(?:[^"\\]|\\.)*"|'[^']*'|[A-Za-z0-9_.\-]+)[ \t]*[=:](.*)$"#,
+            )
+            .unwrap(),
+            toml_table: Regex::new(r"^[ \t]*\[\[?([^\[\]]+)\]\]?[ \t]*(?:[#;].*)?$").unwrap(),
+            yaml_line: Regex::new(
+                r#"^([ \t]*(?:-[ \t]+)?)("(?:[^"\\]|\\.)*"|'[^']*'|[^\s#'"\-][^:#]*?)[ \t]*:(?:[ \t]+(.*))?$"#,
+            )
+            .unwrap(),
+            yaml_item: Regex::new(r"^([ \t]*)-[ \t]+(.*)$").unwrap(),
+        }
+    }
+
+    /// Retorna los rangos de bytes de los valores sensibles, ordenados.
+    pub fn find(&self, text: &str, format: ConfigFormat) -> Vec<(usize, usize)> {
+        let mut spans = match format {
+            ConfigFormat::Json => self.find_json(text),
+            ConfigFormat::Yaml => self.find_yaml(text),
+            ConfigFormat::Toml => self.find_toml(text),
+            ConfigFormat::Dotenv => self.find_dotenv(text),
+        };
+        spans.sort();
+        spans
+    }
+
+    fn is_sensitive(&self, key: &str) -> bool {
+        let words = key_words(key.trim_matches(|c| c == '"' || c == '\''));
+        self.keys
+            .iter()
+            .any(|entry| words.windows(entry.len()).any(|w| w == entry.as_slice()))
+    }
+
+    fn find_dotenv(&self, text: &str) -> Vec<(usize, usize)> {
+        let mut spans = Vec::new();
+        for (offset, line) in lines(text) {
+            let Some(caps) = self.dotenv_line.captures(line) else {
+                continue;
+            };
+            if self.is_sensitive(&caps[1]) {
+                let value = caps.get(2).unwrap();
+                spans.extend(value_span(value.as_str(), offset + value.start()));
+            }
+        }
+        spans
+    }
+
+    /// `clave = valor` bajo secciones `[tabla]`. Una tabla sensible
+    /// (`[secrets]`) hace sensibles todas sus claves.
+    fn find_toml(&self, text: &str) -> Vec<(usize, usize)> {
+        let mut spans = Vec::new();
+        let mut table_sensitive = false;
+        for (offset, line) in lines(text) {
+            if let Some(caps) = self.toml_table.captures(line) {
+                table_sensitive = self.is_sensitive(&caps[1]);
+                continue;
+            }
+            let Some(caps) = self.toml_line.captures(line) else {
+                continue;
+            };
+            if table_sensitive || self.is_sensitive(&caps[1]) {
+                let value = caps.get(2).unwrap();
+                spans.extend(value_span(value.as_str(), offset + value.start()));
+            }
+        }
+        spans
+    }
+
+    /// `clave: valor` con anidamiento por indentacion. Una clave sensible sin
+    /// valor (`credentials:`) hace sensible todo lo que cuelga de ella, y un
+    /// bloque `|` o `>` bajo una clave sensible se marca linea por linea.
+    fn find_yaml(&self, text: &str) -> Vec<(usize, usize)> {
+        struct Section {
+            indent: usize,
+            sensitive: bool,
+            block: bool,
+        }
+
+        let mut spans = Vec::new();
+        let mut sections: Vec<Section> = Vec::new();
+        for (offset, line) in lines(text) {
+            let content = line.trim_start();
+            if content.is_empty() || content.starts_with('#') {
+                continue;
+            }
+            let indent = line.len() - content.len();
+
+            // Lineas de un bloque literal: se marcan enteras
+            if let Some(section) = sections.last() {
+                if section.block && indent > section.indent {
+                    if section.sensitive {
+                        spans.push((offset + indent, offset + line.trim_end().len()));
+                    }
+                    continue;
+                }
+            }
+            while sections.last().is_some_and(|s| s.indent >= indent) {
+                sections.pop();
+            }
+            let inherited = sections.last().is_some_and(|s| s.sensitive);
+
+            if let Some(caps) = self.yaml_line.captures(line) {
+                let sensitive = inherited || self.is_sensitive(&caps[2]);
+                let value = caps.get(3).filter(|v| !v.as_str().trim().starts_with('#'));
+                match value {
+                    Some(v) if v.as_str().starts_with('|') || v.as_str().starts_with('>') => {
+                        sections.push(Section {
+                            indent: caps.get(1).unwrap().len(),
+                            sensitive,
+                            block: true,
+                        });
+                    }
+                    Some(v) if !v.as_str().trim().is_empty() => {
+                        if sensitive {
+                            spans.extend(value_span(v.as_str(), offset + v.start()));
+                        }
+                    }
+                    // Clave sin valor: abre una seccion anidada
+                    _ => sections.push(Section {
+                        indent: caps.get(1).unwrap().len(),
+                        sensitive,
+                        block: false,
+                    }),
+                }
+            } else if let Some(caps) = self.yaml_item.captures(line) {
+                // Elemento de lista sin clave bajo una seccion sensible
+                if inherited {
+                    let value = caps.get(2).unwrap();
+                    spans.extend(value_span(value.as_str(), offset + value.start()));
+                }
+            }
+        }
+        spans
+    }
+
+    /// Recorre los tokens del JSON (admite comentarios de JSONC). Una clave
+    /// sensible con un objeto o arreglo como valor hace sensible todo su
+    /// contenido. Si la ventana empieza dentro de un objeto, los cierres sin
+    /// apertura se ignoran; un string sin cerrar en la linea se salta.
+    fn find_json(&self, text: &str) -> Vec<(usize, usize)> {
+        let bytes = text.as_bytes();
+        let mut spans = Vec::new();
+        // Contenedores abiertos: true si estan bajo una clave sensible
+        let mut stack: Vec<bool> = Vec::new();
+        // Sensibilidad de la ultima clave leida, mientras espera su valor
+        let mut pending: Option<bool> = None;
+        let mut i = 0;
+
+        while i < bytes.len() {
+            let inherited = stack.last().copied().unwrap_or(false);
+            match bytes[i] {
+                b'"' => {
+                    let Some(end) = json_string_end(bytes, i) else {
+                        // String roto: saltar al final de la linea
+                        i = next_line_start(bytes, i);
+                        continue;
+                    };
+                    let next = skip_whitespace(bytes, end + 1);
+                    if bytes.get(next) == Some(&b':') {
+                        pending = Some(inherited || self.is_sensitive(&text[i + 1..end]));
+                        i = next + 1;
+                        continue;
+                    }
+                    if pending.take().unwrap_or(inherited) && end > i + 1 {
+                        spans.push((i + 1, end));
+                    }
+                    i = end + 1;
+                }
+                b'{' | b'[' => {
+                    stack.push(pending.take().unwrap_or(inherited));
+                    i += 1;
+                }
+                b'}' | b']' => {
+                    stack.pop();
+                    pending = None;
+                    i += 1;
+                }
+                b',' => {
+                    pending = None;
+                    i += 1;
+                }
+                b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                    i = next_line_start(bytes, i);
+                }
+                b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                    i = text[i + 2..]
+                        .find("*/")
+                        .map_or(bytes.len(), |p| i + 2 + p + 2);
+                }
+                b'-' | b'0'..=b'9' => {
+                    let end = i + bytes[i..]
+                        .iter()
+                        .take_while(|b| {
+                            b.is_ascii_digit() || matches!(b, b'-' | b'+' | b'.' | b'e' | b'E')
+                        })
+                        .count();
+                    if pending.take().unwrap_or(inherited) {
+                        spans.push((i, end));
+                    }
+                    i = end;
+                }
+                _ => i += 1,
+            }
+        }
+        spans
+    }
+}
+
+/// Parte una clave en palabras en minusculas: `dbPassword` -> [db, password],
+/// `API_KEY` -> [api, key], `APIKey` -> [api, key].
+fn key_words(key: &str) -> Vec<String> {
+    let chars: Vec<char> = key.chars().collect();
+    let mut words = Vec::new();
+    let mut current = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+        if c.is_uppercase() && !current.is_empty() {
+            let prev = chars[i - 1];
+            let next_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+            if prev.is_lowercase() || prev.is_ascii_digit() || (prev.is_uppercase() && next_lower) {
+                words.push(std::mem::take(&mut current));
+            }
+        }
+        current.extend(c.to_lowercase());
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+/// Lineas con su offset en bytes, sin el salto de linea.
+fn lines(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    let mut offset = 0;
+    text.split_inclusive('\n').map(move |raw| {
+        let start = offset;
+        offset += raw.len();
+        (start, raw.trim_end_matches(['\n', '\r']))
+    })
+}
+
+/// Rango del valor de una linea `clave = valor`, sin comillas ni comentario
+/// final. `start` es el offset de `value` en el texto. None si no hay nada
+/// que redactar: vacio, booleano, null, referencia a otra variable
+/// (`${DB_PASS}`) o una estructura anidada (`{`, `[`).
+fn value_span(value: &str, start: usize) -> Option<(usize, usize)> {
+    let v = value.trim_start();
+    let start = start + (value.len() - v.len());
+    let quote = v.chars().next()?;
+
+    if quote == '"' || quote == '\'' {
+        let inner = &v[1..];
+        let end = closing_quote(inner, quote).unwrap_or_else(|| inner.trim_end().len());
+        return (end > 0).then_some((start + 1, start + 1 + end));
+    }
+    if matches!(quote, '{' | '[' | '|' | '>' | '&' | '*' | '!' | '$') {
+        return None;
+    }
+
+    let v = v.find(" #").map_or(v, |pos| &v[..pos]).trim_end();
+    if v.is_empty()
+        || matches!(
+            v.to_ascii_lowercase().as_str(),
+            "true" | "false" | "null" | "~"
+        )
+    {
+        return None;
+    }
+    Some((start, start + v.len()))
+}
+
+/// Posicion de la comilla de cierre en `inner` (texto tras la de apertura).
+fn closing_quote(inner: &str, quote: char) -> Option<usize> {
+    let mut escaped = false;
+    for (i, c) in inner.char_indices() {
+        match c {
+            '\\' if quote == '"' && !escaped => escaped = true,
+            c if c == quote && !escaped => return Some(i),
+            _ => escaped = false,
+        }
+    }
+    None
+}
+
+/// Indice de la comilla que cierra el string JSON que empieza en `start`.
+/// None si la linea termina antes.
+fn json_string_end(bytes: &[u8], start: usize) -> Option<usize> {
+    let mut i = start + 1;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'"' => return Some(i),
+            b'\n' => return None,
+            _ => i += 1,
+        }
+    }
+    None
+}
+
+fn skip_whitespace(bytes: &[u8], mut i: usize) -> usize {
+    while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+        i += 1;
+    }
+    i
+}
+
+fn next_line_start(bytes: &[u8], start: usize) -> usize {
+    bytes[start..]
+        .iter()
+        .position(|&b| b == b'\n')
+        .map_or(bytes.len(), |p| start + p + 1)
+}
+
+// ---------------------------------------------------------------------------
+// Tests unitarios
+// ---------------------------------------------------------------------------
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detector() -> StructuredDetector {
+        StructuredDetector::new(&[])
+    }
+
+    /// Valores marcados, para comparar facil.
+    fn found(text: &str, format: ConfigFormat) -> Vec<&str> {
+        detector()
+            .find(text, format)
+            .into_iter()
+            .map(|(s, e)| &text[s..e])
+            .collect()
+    }
+
+    #[test]
+    fn test_detectar_formato() {
+        assert_eq!(
+            ConfigFormat::detect("yaml", "/x/a.txt"),
+            Some(ConfigFormat::Yaml)
+        );
+        assert_eq!(
+            ConfigFormat::detect("plaintext", "/app/.env.local"),
+            Some(ConfigFormat::Dotenv)
+        );
+        assert_eq!(
+            ConfigFormat::detect("plaintext", "/app/prod.env"),
+            Some(ConfigFormat::Dotenv)
+        );
+        assert_eq!(
+            ConfigFormat::detect("plaintext", "/app/config.yml"),
+            Some(ConfigFormat::Yaml)
+        );
+        assert_eq!(ConfigFormat::detect("ini", ""), Some(ConfigFormat::Toml));
+        assert_eq!(ConfigFormat::detect("rust", "/src/main.rs"), None);
+    }
+
+    #[test]
+    fn test_palabras_de_clave() {
+        assert_eq!(key_words("dbPassword"), ["db", "password"]);
+        assert_eq!(key_words("APIKey"), ["api", "key"]);
+        assert_eq!(
+            key_words("spring.datasource.password"),
+            ["spring", "datasource", "password"]
+        );
+        let d = detector();
+        assert!(d.is_sensitive("DB_PASS"));
+        assert!(d.is_sensitive("apiKey"));
+        assert!(!d.is_sensitive("passport"));
+        assert!(!d.is_sensitive("bypass"));
+        assert!(!d.is_sensitive("key"));
+    }
+
+    #[test]
+    fn test_dotenv() {
+        let text = "DB_HOST=localhost\nDB_PASS=foo\nexport API_KEY='abc 123' # prod\nJWT_SECRET=${SECRET}\n";
+        assert_eq!(found(text, ConfigFormat::Dotenv), ["foo", "abc 123"]);
+    }
+
+    #[test]
+    fn test_yaml_sin_comillas_y_anidado() {
+        let text = concat!(
+            "database:\n",
+            "  user: admin\n",
+            "  password: hunter2 # cambiar\n",
+            "credentials:\n",
+            "  stripe: sk_abc\n",
+            "  list:\n",
+            "    - uno\n",
+            "debug: true\n",
+            "name: lia\n",
+        );
+        assert_eq!(
+            found(text, ConfigFormat::Yaml),
+            ["hunter2", "sk_abc", "uno"]
+        );
+    }
+
+    #[test]
+    fn test_yaml_bloque_literal() {
+        let text = "private_key: |\n  linea1\n  linea2\nother: x\n";
+        assert_eq!(found(text, ConfigFormat::Yaml), ["linea1", "linea2"]);
+    }
+
+    #[test]
+    fn test_toml_y_tablas_sensibles() {
+        let text = concat!(
+            "[database]\n",
+            "host = \"db\"\n",
+            "password = \"p@ss\"\n",
+            "[secrets]\n",
+            "stripe = \"sk_test\"\n",
+            "port = 5432\n",
+        );
+        assert_eq!(found(text, ConfigFormat::Toml), ["p@ss", "sk_test", "5432"]);
+    }
+
+    #[test]
+    fn test_json_anidado() {
+        let text = r#"{"db": {"host": "x", "password": "hunter2", "pin": 1234},
+            "auth": {"apiKey": "abc"}, "credentials": {"a": "b", "c": ["d"]}}"#;
+        assert_eq!(
+            found(text, ConfigFormat::Json),
+            ["hunter2", "abc", "b", "d"]
+        );
+    }
+
+    #[test]
+    fn test_json_fragmento_malformado() {
+        // Ventana que empieza dentro de un objeto y corta un string
+        let text =
+            "    \"token\": \"abc\"\n  },\n  \"secret\": \"sin cerrar\n  \"password\": \"xyz\"\n";
+        assert_eq!(found(text, ConfigFormat::Json), ["abc", "xyz"]);
+    }
+}
@@ -0,0 +1,133 @@
+// lia-client/src-tauri/src/tiles.rs
+// Rejilla de mosaicos: parte la captura en cuadrados de tamano fijo para que
+// el Contrato B lleve solo los que cambiaron desde la captura anterior.
+// Cada mosaico se codifica por separado y se identifica por el SHA-256 de sus
+// bytes; el Cloud los guarda en su cache y rearma el frame con el layout.
+
+use image::DynamicImage;
+
+use crate::hasher;
+
+/// Lado minimo de un mosaico: por debajo las cabeceras de cada imagen pesan
+/// mas que los pixeles.
+const MIN_TILE_SIZE: u32 = 32;
+
+/// Un mosaico codificado.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tile {
+    /// SHA-256 de `data`: clave del cache de mosaicos del Cloud.
+    pub hash: String,
+    pub data: Vec<u8>,
+}
+
+/// Captura partida en mosaicos de `tile_size` px, fila por fila. Los de la
+/// ultima columna y la ultima fila pueden ser mas pequenos.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TileGrid {
+    pub tile_size: u32,
+    pub columns: u32,
+    pub rows: u32,
+    pub tiles: Vec<Tile>,
+}
+
+impl TileGrid {
+    /// Parte la imagen y codifica cada mosaico con `encode`.
+    pub fn split<F>(image: &DynamicImage, tile_size: u32, mut encode: F) -> Result<Self, String>
+    where
+        F: FnMut(&DynamicImage) -> Result<Vec<u8>, String>,
+    {
+        let tile_size = tile_size.max(MIN_TILE_SIZE);
+        let (width, height) = (image.width(), image.height());
+        let columns = width.div_ceil(tile_size);
+        let rows = height.div_ceil(tile_size);
+
+        let mut tiles = Vec::with_capacity((columns * rows) as usize);
+        for row in 0..rows {
+            for column in 0..columns {
+                let x = column * tile_size;
+                let y = row * tile_size;
+                let tile =
+                    image.crop_imm(x, y, tile_size.min(width - x), tile_size.min(height - y));
+                let data = encode(&tile)?;
+                tiles.push(Tile {
+                    hash: hasher::compute_sha256_bytes(&data),
+                    data,
+                });
+            }
+        }
+
+        Ok(TileGrid {
+            tile_size,
+            columns,
+            rows,
+            tiles,
+        })
+    }
+
+    /// Hash de cada mosaico, fila por fila (el manifiesto del Contrato B).
+    pub fn layout(&self) -> Vec<String> {
+        self.tiles.iter().map(|t| t.hash.clone()).collect()
+    }
+
+    /// Hash del frame completo: identifica la combinacion de mosaicos.
+    pub fn frame_hash(&self) -> String {
+        hasher::compute_sha256(&format!(
+            "{}x{}:{}",
+            self.columns,
+            self.rows,
+            self.layout().join(",")
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgb, RgbImage};
+
+    fn png(tile: &DynamicImage) -> Result<Vec<u8>, String> {
+        let mut buffer = std::io::Cursor::new(Vec::new());
+        tile.write_to(&mut buffer, image::ImageFormat::Png)
+            .map_err(|e| e.to_string())?;
+        Ok(buffer.into_inner())
+    }
+
+    fn lienzo(width: u32, height: u32) -> DynamicImage {
+        DynamicImage::ImageRgb8(RgbImage::from_fn(width, height, |x, y| {
+            Rgb([(x % 251) as u8, (y % 241) as u8, ((x + y) % 239) as u8])
+        }))
+    }
+
+    #[test]
+    fn test_rejilla_con_bordes_parciales() {
+        let grid = TileGrid::split(&lienzo(300, 130), 128, png).unwrap();
+        assert_eq!((grid.columns, grid.rows), (3, 2));
+        assert_eq!(grid.tiles.len(), 6);
+
+        let ultimo = image::load_from_memory(&grid.tiles[5].data).unwrap();
+        assert_eq!((ultimo.width(), ultimo.height()), (44, 2));
+    }
+
+    #[test]
+    fn test_un_pixel_cambia_un_mosaico() {
+        let antes = TileGrid::split(&lienzo(256, 256), 64, png).unwrap();
+
+        let mut cambiada = lienzo(256, 256).to_rgb8();
+        cambiada.put_pixel(200, 10, Rgb([255, 0, 0]));
+        let despues = TileGrid::split(&DynamicImage::ImageRgb8(cambiada), 64, png).unwrap();
+
+        let distintos: Vec<usize> = (0..antes.tiles.len())
+            .filter(|&i| antes.tiles[i].hash != despues.tiles[i].hash)
+            .collect();
+        // Columna 3, fila 0
+        assert_eq!(distintos, vec![3]);
+        assert_ne!(antes.frame_hash(), despues.frame_hash());
+    }
+
+    #[test]
+    fn test_tamano_minimo() {
+        let grid = TileGrid::split(&lienzo(64, 64), 1, png).unwrap();
+        assert_eq!(grid.tile_size, MIN_TILE_SIZE);
+        assert_eq!(grid.tiles.len(), 4);
+    }
+}
@@ -1,9 +1,551 @@
 // lia-client/src-tauri/src/vision.rs
 // Modulo de vision: captura de pantalla con soporte multi-monitor.
+// Captura un monitor, la ventana enfocada o una region fija de una
+// `ScreenSource` (la pantalla real o una de prueba), tapa en negro
+// las ventanas de apps sensibles (gestores de contrasenas, chats,
+// terminales) y reduce y codifica la imagen para el Contrato B, o extrae su
+// texto con OCR local si la imagen no debe salir de la maquina.
 
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::webp::WebPEncoder;
+use image::imageops::FilterType;
+use image::{DynamicImage, Rgba, RgbaImage};
+use serde::{Deserialize, Serialize};
 use std::time::Instant;
 use xcap::Monitor;
 
+use crate::hasher;
+use crate::ocr::{OcrConfig, OcrEngine};
+use crate::screen_source::{MonitorInfo, ScreenSource, SourceConfig, WindowInfo};
+use crate::tiles::TileGrid;
+
+/// Apps que se tapan por defecto. Se comparan, sin distinguir mayusculas,
+/// como subcadena del nombre de la app (no del titulo, que cambia con el
+/// archivo o la pestana abierta).
+const BUILTIN_BLOCKLIST: &[&str] = &[
+    // Gestores de contrasenas
+    "1password",
+    "bitwarden",
+    "keepass",
+    "lastpass",
+    "dashlane",
+    "enpass",
+    "keychain access",
+    "seahorse",
+    "kwalletmanager",
+    // Chats
+    "slack",
+    "discord",
+    "telegram",
+    "whatsapp",
+    "signal",
+    "microsoft teams",
+    // Terminales
+    "terminal",
+    "iterm",
+    "konsole",
+    "alacritty",
+    "kitty",
+    "wezterm",
+    "tilix",
+    "powershell",
+];
+
+const MASK_COLOR: Rgba<u8> = Rgba([0, 0, 0, 255]);
+
+// ---------------------------------------------------------------------------
+// Configuracion ([screen] en ~/.lia/config.toml)
+// ---------------------------------------------------------------------------
+
+/// Seccion `[screen]` de config.toml.
+///
+/// ```toml
+/// [screen]
+/// builtin_blocklist = true
+/// blocklist = ["Banco", "Thunderbird"]
+/// similarity_threshold = 3
+///
+/// [screen.capture]
+/// mode = "monitor"
+/// monitor = "DP-1"
+///
+/// [screen.encoding]
+/// format = "jpeg"
+/// quality = 80
+/// max_edge = 1920
+/// tile_size = 256
+///
+/// [screen.source]
+/// kind = "xcap"
+///
+/// [screen.ocr]
+/// enabled = false
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields, default)]
+pub struct ScreenConfig {
+    /// Modo de captura por defecto; `ask_lia` puede cambiarlo por peticion.
+    pub capture: CaptureMode,
+    /// Tapar la lista integrada de apps sensibles.
+    pub builtin_blocklist: bool,
+    /// Patrones del usuario; se buscan en el nombre de la app y en el titulo.
+    pub blocklist: Vec<String>,
+    /// Reduccion y formato de la imagen enviada.
+    pub encoding: EncodeConfig,
+    /// Bits de dHash (de 64) que puede diferir una captura de la anterior
+    /// para reusar la del cache del Cloud en vez de reenviarla.
+    pub similarity_threshold: u32,
+    /// De donde salen las capturas: la pantalla real o una fuente de prueba.
+    pub source: SourceConfig,
+    /// Enviar el texto de la pantalla (OCR local) en vez de la imagen.
+    pub ocr: OcrConfig,
+}
+
+impl Default for ScreenConfig {
+    fn default() -> Self {
+        ScreenConfig {
+            capture: CaptureMode::default(),
+            builtin_blocklist: true,
+            blocklist: Vec::new(),
+            encoding: EncodeConfig::default(),
+            similarity_threshold: 3,
+            source: SourceConfig::default(),
+            ocr: OcrConfig::default(),
+        }
+    }
+}
+
+/// Seccion `[screen.encoding]`: como se reduce y codifica la captura.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields, default)]
+pub struct EncodeConfig {
+    pub format: EncodeFormat,
+    /// Calidad JPEG de 1 a 100. WebP se codifica sin perdidas.
+    pub quality: u8,
+    /// Lado mayor maximo en pixeles; 0 para no reducir.
+    pub max_edge: u32,
+    /// Enviar en escala de grises (menos bytes, suficiente para leer codigo).
+    pub grayscale: bool,
+    /// Lado de los mosaicos en pixeles: solo se reenvian los que cambian.
+    /// 0 envia siempre la imagen completa.
+    pub tile_size: u32,
+}
+
+impl Default for EncodeConfig {
+    fn default() -> Self {
+        // Una captura 4K en PNG ocupa varios MB; en JPEG a 1920 unos 200-400 KB
+        EncodeConfig {
+            format: EncodeFormat::Jpeg,
+            quality: 80,
+            max_edge: 1920,
+            grayscale: false,
+            tile_size: 256,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EncodeFormat {
+    Png,
+    Jpeg,
+    Webp,
+}
+
+impl EncodeFormat {
+    pub fn mime_type(self) -> &'static str {
+        match self {
+            EncodeFormat::Png => "image/png",
+            EncodeFormat::Jpeg => "image/jpeg",
+            EncodeFormat::Webp => "image/webp",
+        }
+    }
+}
+
+/// Que parte de la pantalla se captura.
+#[derive(Debug, Clone, PartialEq, Default, Deserialize, Serialize)]
+#[serde(tag = "mode", rename_all = "snake_case", deny_unknown_fields)]
+pub enum CaptureMode {
+    /// Un monitor completo. Sin selector se usa el primero.
+    Monitor {
+        #[serde(default)]
+        monitor: Option<MonitorSelector>,
+    },
+    /// El monitor donde esta la ventana enfocada (donde suele estar el editor).
+    #[default]
+    FocusedMonitor,
+    /// Solo la ventana enfocada.
+    FocusedWindow,
+    /// Region fija en coordenadas globales de pantalla.
+    Region {
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+    },
+}
+
+impl CaptureMode {
+    /// Nombre del modo para el Contrato B.
+    pub fn name(&self) -> &'static str {
+        match self {
+            CaptureMode::Monitor { .. } => "monitor",
+            CaptureMode::FocusedMonitor => "focused_monitor",
+            CaptureMode::FocusedWindow => "focused_window",
+            CaptureMode::Region { .. } => "region",
+        }
+    }
+}
+
+/// Monitor elegido por indice (orden de xcap) o por nombre.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum MonitorSelector {
+    Index(usize),
+    Name(String),
+}
+
+/// Busca el monitor pedido entre los nombres disponibles.
+fn select_monitor(names: &[&str], selector: &MonitorSelector) -> Result<usize, String> {
+    match selector {
+        MonitorSelector::Index(i) if *i < names.len() => Ok(*i),
+        MonitorSelector::Index(i) => {
+            Err(format!("No existe el monitor {} (hay {})", i, names.len()))
+        }
+        MonitorSelector::Name(name) => names
+            .iter()
+            .position(|n| n.eq_ignore_ascii_case(name))
+            .ok_or_else(|| {
+                format!(
+                    "No existe el monitor '{}' (disponibles: {})",
+                    name,
+                    names.join(", ")
+                )
+            }),
+    }
+}
+
+/// Lista compilada de ventanas que no deben verse en la captura.
+#[derive(Debug, Clone, Default)]
+pub struct WindowBlocklist {
+    builtin: bool,
+    /// Patrones del usuario en minusculas.
+    patterns: Vec<String>,
+}
+
+impl WindowBlocklist {
+    pub fn from_config(config: &ScreenConfig) -> Result<Self, String> {
+        let mut patterns = Vec::with_capacity(config.blocklist.len());
+        for pattern in &config.blocklist {
+            let pattern = pattern.trim();
+            // Un patron vacio taparia todas las ventanas
+            if pattern.is_empty() {
+                return Err("[screen] blocklist contiene un patron vacio".to_string());
+            }
+            patterns.push(pattern.to_lowercase());
+        }
+        Ok(WindowBlocklist {
+            builtin: config.builtin_blocklist,
+            patterns,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        !self.builtin && self.patterns.is_empty()
+    }
+
+    /// Indica si una ventana debe taparse.
+    pub fn matches(&self, app_name: &str, title: &str) -> bool {
+        let app_name = app_name.to_lowercase();
+        if self.builtin && BUILTIN_BLOCKLIST.iter().any(|b| app_name.contains(b)) {
+            return true;
+        }
+        let title = title.to_lowercase();
+        self.patterns
+            .iter()
+            .any(|p| app_name.contains(p.as_str()) || title.contains(p.as_str()))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Captura
+// ---------------------------------------------------------------------------
+
+/// Captura lista para empaquetar en el Contrato B.
+#[derive(Debug, Clone, Default)]
+pub struct ScreenCapture {
+    /// Imagen codificada. Vacia si no hubo captura o si se partio en mosaicos.
+    pub data: Vec<u8>,
+    /// Mosaicos codificados, si `tile_size` no es 0.
+    pub tiles: Option<TileGrid>,
+    /// Formato de `data` o de cada mosaico. `None` si no hubo captura o si
+    /// se envia el texto.
+    pub format: Option<EncodeFormat>,
+    /// Texto extraido por OCR local, aun sin sanitizar. Si esta, no hay
+    /// imagen ni mosaicos.
+    pub text: Option<String>,
+    /// Modo usado. `None` si no hubo captura.
+    pub mode: Option<CaptureMode>,
+    /// Dimensiones en pixeles de la imagen enviada (ya reducida).
+    pub width: u32,
+    pub height: u32,
+    /// Hash perceptual de la imagen enviada, para el Smart Caching.
+    pub dhash: u64,
+    /// Nombre de la app de cada ventana tapada. Solo para logs locales;
+    /// al Cloud solo viaja el conteo.
+    pub masked_windows: Vec<String>,
+}
+
+/// Rectangulo en coordenadas globales de pantalla (pueden ser negativas
+/// con varios monitores).
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ScreenRect {
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+}
+
+impl ScreenRect {
+    fn of_monitor(monitor: &MonitorInfo) -> Self {
+        ScreenRect {
+            x: monitor.x,
+            y: monitor.y,
+            width: monitor.width,
+            height: monitor.height,
+        }
+    }
+
+    fn of_window(window: &WindowInfo) -> Self {
+        ScreenRect {
+            x: window.x,
+            y: window.y,
+            width: window.width,
+            height: window.height,
+        }
+    }
+
+    /// Parte comun de dos rectangulos. `None` si no se solapan.
+    fn intersect(&self, other: &ScreenRect) -> Option<ScreenRect> {
+        let left = self.x.max(other.x) as i64;
+        let top = self.y.max(other.y) as i64;
+        let right = (self.x as i64 + self.width as i64).min(other.x as i64 + other.width as i64);
+        let bottom = (self.y as i64 + self.height as i64).min(other.y as i64 + other.height as i64);
+        if right <= left || bottom <= top {
+            return None;
+        }
+        Some(ScreenRect {
+            x: left as i32,
+            y: top as i32,
+            width: (right - left) as u32,
+            height: (bottom - top) as u32,
+        })
+    }
+
+    fn area(&self) -> u64 {
+        self.width as u64 * self.height as u64
+    }
+}
+
+/// Indice del monitor que mas se solapa con un rectangulo.
+fn monitor_with_most_overlap(monitors: &[ScreenRect], rect: &ScreenRect) -> Option<usize> {
+    monitors
+        .iter()
+        .enumerate()
+        .filter_map(|(i, m)| m.intersect(rect).map(|common| (i, common.area())))
+        .max_by_key(|&(i, area)| (area, std::cmp::Reverse(i)))
+        .map(|(i, _)| i)
+}
+
+/// Rectangulo en pixeles de la imagen capturada.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct PixelRect {
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+}
+
+/// Proyecta un rectangulo de pantalla sobre la imagen de un area capturada
+/// (un monitor o un recorte). La escala se deduce del tamano de la imagen,
+/// asi funciona igual con pantallas HiDPI donde las coordenadas de ventana
+/// son logicas. `None` si no se solapan.
+fn project_onto_image(
+    window: ScreenRect,
+    area: ScreenRect,
+    image_size: (u32, u32),
+) -> Option<PixelRect> {
+    if area.width == 0 || area.height == 0 {
+        return None;
+    }
+    let scale_x = image_size.0 as f64 / area.width as f64;
+    let scale_y = image_size.1 as f64 / area.height as f64;
+
+    // Bordes relativos al area, en pixeles. Se redondea hacia afuera
+    // para no dejar franjas de la ventana visibles.
+    let edge = |offset: i64, scale: f64, limit: u32, round: fn(f64) -> f64| -> u32 {
+        round(offset as f64 * scale).clamp(0.0, limit as f64) as u32
+    };
+    let dx = window.x as i64 - area.x as i64;
+    let dy = window.y as i64 - area.y as i64;
+    let left = edge(dx, scale_x, image_size.0, f64::floor);
+    let top = edge(dy, scale_y, image_size.1, f64::floor);
+    let right = edge(dx + window.width as i64, scale_x, image_size.0, f64::ceil);
+    let bottom = edge(dy + window.height as i64, scale_y, image_size.1, f64::ceil);
+
+    if right <= left || bottom <= top {
+        return None;
+    }
+    Some(PixelRect {
+        x: left,
+        y: top,
+        width: right - left,
+        height: bottom - top,
+    })
+}
+
+/// Pinta un rectangulo opaco sobre la imagen.
+fn mask_rect(image: &mut RgbaImage, rect: PixelRect) {
+    for y in rect.y..rect.y + rect.height {
+        for x in rect.x..rect.x + rect.width {
+            image.put_pixel(x, y, MASK_COLOR);
+        }
+    }
+}
+
+/// Reduce la imagen al lado maximo y la pasa a grises si se pide.
+/// Se descarta el canal alfa: una captura de pantalla es opaca.
+fn downscale(image: RgbaImage, config: &EncodeConfig) -> DynamicImage {
+    let mut image = DynamicImage::ImageRgba8(image);
+    let (width, height) = (image.width(), image.height());
+    if config.max_edge > 0 && width.max(height) > config.max_edge {
+        // Triangle: casi igual de legible que Lanczos3 para texto y mucho mas rapido
+        image = image.resize(config.max_edge, config.max_edge, FilterType::Triangle);
+    }
+    if config.grayscale {
+        DynamicImage::ImageLuma8(image.to_luma8())
+    } else {
+        DynamicImage::ImageRgb8(image.to_rgb8())
+    }
+}
+
+/// Codifica la imagen ya reducida en el formato configurado.
+fn write_image(image: &DynamicImage, config: &EncodeConfig) -> Result<Vec<u8>, String> {
+    let mut buffer = std::io::Cursor::new(Vec::new());
+    let result = match config.format {
+        EncodeFormat::Png => image.write_to(&mut buffer, image::ImageFormat::Png),
+        EncodeFormat::Jpeg => image.write_with_encoder(JpegEncoder::new_with_quality(
+            &mut buffer,
+            config.quality.clamp(1, 100),
+        )),
+        EncodeFormat::Webp => image.write_with_encoder(WebPEncoder::new_lossless(&mut buffer)),
+    };
+    result.map_err(|e| format!("Error al codificar {}: {}", config.format.mime_type(), e))?;
+    Ok(buffer.into_inner())
+}
+
+/// Tapa las ventanas bloqueadas que se solapan con el area capturada.
+/// Se tapa el rectangulo completo aunque otra ventana lo cubra en parte:
+/// es mas barato y nunca deja ver de menos.
+fn mask_windows(
+    image: &mut RgbaImage,
+    area: ScreenRect,
+    source: &dyn ScreenSource,
+    blocklist: &WindowBlocklist,
+) -> Result<Vec<String>, String> {
+    if blocklist.is_empty() {
+        return Ok(Vec::new());
+    }
+    let windows = source.windows()?;
+
+    let mut masked = Vec::new();
+    for window in &windows {
+        if window.is_minimized || !blocklist.matches(&window.app_name, &window.title) {
+            continue;
+        }
+        let window_rect = ScreenRect::of_window(window);
+        if let Some(rect) = project_onto_image(window_rect, area, image.dimensions()) {
+            mask_rect(image, rect);
+            masked.push(window.app_name.clone());
+        }
+    }
+    Ok(masked)
+}
+
+/// Rectangulo de la ventana enfocada, ignorando las ventanas de Lia (el HUD
+/// tiene el foco cuando el usuario pulsa su boton).
+fn focused_window(source: &dyn ScreenSource) -> Result<Option<ScreenRect>, String> {
+    let own_pid = std::process::id();
+    let windows = source.windows()?;
+    Ok(windows
+        .iter()
+        .find(|w| w.is_focused && !w.is_minimized && w.pid != own_pid)
+        .map(ScreenRect::of_window))
+}
+
+/// Decide que monitor capturar y que area de la pantalla recortar.
+fn resolve_capture_area(
+    mode: &CaptureMode,
+    monitors: &[MonitorInfo],
+    source: &dyn ScreenSource,
+) -> Result<(usize, ScreenRect), String> {
+    let rects: Vec<ScreenRect> = monitors.iter().map(ScreenRect::of_monitor).collect();
+
+    let (index, area) = match mode {
+        CaptureMode::Monitor { monitor } => {
+            let index = match monitor {
+                Some(selector) => {
+                    let names: Vec<&str> = monitors.iter().map(|m| m.name.as_str()).collect();
+                    select_monitor(&names, selector)?
+                }
+                None => 0,
+            };
+            (index, rects[index])
+        }
+        CaptureMode::FocusedMonitor => {
+            let focused = focused_window(source).unwrap_or_else(|e| {
+                eprintln!("{}", e);
+                None
+            });
+            let index = focused
+                .and_then(|window| monitor_with_most_overlap(&rects, &window))
+                .unwrap_or_else(|| {
+                    println!("Sin ventana enfocada, se captura el monitor 0");
+                    0
+                });
+            (index, rects[index])
+        }
+        CaptureMode::FocusedWindow => {
+            let window = focused_window(source)?.ok_or("No hay ninguna ventana enfocada")?;
+            let index = monitor_with_most_overlap(&rects, &window)
+                .ok_or("La ventana enfocada no esta en ningun monitor")?;
+            (index, window)
+        }
+        CaptureMode::Region {
+            x,
+            y,
+            width,
+            height,
+        } => {
+            let region = ScreenRect {
+                x: *x,
+                y: *y,
+                width: *width,
+                height: *height,
+            };
+            let index = monitor_with_most_overlap(&rects, &region)
+                .ok_or("La region no se solapa con ningun monitor")?;
+            (index, region)
+        }
+    };
+
+    // Una ventana o region que cruza monitores se recorta al elegido
+    let area = area
+        .intersect(&rects[index])
+        .ok_or("El area a capturar esta vacia")?;
+    Ok((index, area))
+}
+
 /// Prueba basica de vision (Fase 1, solo diagnostico).
 pub fn probar_vision() {
     println!("Iniciando prueba de vision...");
@@ -40,10 +582,21 @@ pub fn probar_vision() {
     }
 }
 
-/// Captura la pantalla del monitor primario y retorna los bytes PNG.
-/// Soportado multi-monitor: usa el primer monitor disponible.
-pub fn capture_screen() -> Result<Vec<u8>, String> {
-    let monitores = Monitor::all().map_err(|e| format!("Error al enumerar monitores: {}", e))?;
+/// Captura la pantalla segun el modo, tapa las ventanas de la blocklist
+/// y retorna la imagen reducida y codificada segun `encoding`. Con `ocr`
+/// retorna solo el texto de la captura tapada.
+///
+/// Si la blocklist no esta vacia y no se pueden enumerar las ventanas
+/// (p. ej. en algunos compositores Wayland) la captura falla: es preferible
+/// no enviar imagen a enviar una sin tapar.
+pub fn capture_screen(
+    source: &dyn ScreenSource,
+    mode: &CaptureMode,
+    blocklist: &WindowBlocklist,
+    encoding: &EncodeConfig,
+    ocr: Option<&OcrEngine>,
+) -> Result<ScreenCapture, String> {
+    let monitores = source.monitors()?;
 
     if monitores.is_empty() {
         return Err("No se detecto ningun monitor".to_string());
@@ -51,26 +604,390 @@ pub fn capture_screen() -> Result<Vec<u8>, String> {
 
     // Listar monitores disponibles
     for (i, m) in monitores.iter().enumerate() {
-        println!(
-            "  Monitor {}: {} ({}x{})",
-            i,
-            m.name(),
-            m.width(),
-            m.height()
+        println!("  Monitor {}: {} ({}x{})", i, m.name, m.width, m.height);
+    }
+
+    let (index, area) = resolve_capture_area(mode, &monitores, source)?;
+    let monitor_rect = ScreenRect::of_monitor(&monitores[index]);
+    let mut imagen = source.capture_monitor(index)?;
+
+    // Recortar la ventana o region; las coordenadas pasan a ser las del recorte
+    if area != monitor_rect {
+        let crop = project_onto_image(area, monitor_rect, imagen.dimensions())
+            .ok_or("El area a capturar esta vacia")?;
+        imagen =
+            image::imageops::crop_imm(&imagen, crop.x, crop.y, crop.width, crop.height).to_image();
+    }
+
+    // Tapar ventanas sensibles antes de que los pixeles salgan de aqui
+    let masked_windows = mask_windows(&mut imagen, area, source, blocklist)?;
+
+    // OCR a resolucion completa: reducir antes empeora la lectura del texto
+    if let Some(ocr) = ocr {
+        return Ok(ScreenCapture {
+            text: Some(ocr.recognize(&imagen)?),
+            mode: Some(mode.clone()),
+            width: imagen.width(),
+            height: imagen.height(),
+            masked_windows,
+            ..Default::default()
+        });
+    }
+
+    // Reducir y codificar en memoria
+    let imagen = downscale(imagen, encoding);
+    let (width, height) = (imagen.width(), imagen.height());
+    let dhash = hasher::compute_dhash(&imagen);
+    let (data, tiles) = if encoding.tile_size > 0 {
+        let grid = TileGrid::split(&imagen, encoding.tile_size, |tile| {
+            write_image(tile, encoding)
+        })?;
+        (Vec::new(), Some(grid))
+    } else {
+        (write_image(&imagen, encoding)?, None)
+    };
+
+    Ok(ScreenCapture {
+        data,
+        tiles,
+        format: Some(encoding.format),
+        text: None,
+        mode: Some(mode.clone()),
+        width,
+        height,
+        dhash,
+        masked_windows,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::screen_source::SyntheticSource;
+
+    fn rect(x: i32, y: i32, width: u32, height: u32) -> ScreenRect {
+        ScreenRect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    #[test]
+    fn test_blocklist_integrada() {
+        let blocklist = WindowBlocklist::from_config(&ScreenConfig::default()).unwrap();
+        assert!(blocklist.matches("1Password", "Todos los elementos"));
+        assert!(blocklist.matches("gnome-terminal-server", "~/proyecto"));
+        assert!(blocklist.matches("Slack", "#general"));
+        // El titulo no cuenta para la lista integrada
+        assert!(!blocklist.matches("Code", "terminal.rs - lia"));
+    }
+
+    #[test]
+    fn test_blocklist_de_usuario() {
+        let config = ScreenConfig {
+            builtin_blocklist: false,
+            blocklist: vec!["Banco".to_string()],
+            ..Default::default()
+        };
+        let blocklist = WindowBlocklist::from_config(&config).unwrap();
+        assert!(blocklist.matches("Firefox", "Mi BANCO - Movimientos"));
+        assert!(!blocklist.matches("Slack", "#general"));
+    }
+
+    #[test]
+    fn test_blocklist_vacia() {
+        let config = ScreenConfig {
+            builtin_blocklist: false,
+            blocklist: vec![],
+            ..Default::default()
+        };
+        assert!(WindowBlocklist::from_config(&config).unwrap().is_empty());
+
+        let config = ScreenConfig {
+            builtin_blocklist: false,
+            blocklist: vec!["  ".to_string()],
+            ..Default::default()
+        };
+        assert!(WindowBlocklist::from_config(&config).is_err());
+    }
+
+    #[test]
+    fn test_proyeccion_recorta_al_monitor() {
+        let monitor = rect(0, 0, 100, 50);
+        let window = rect(80, -10, 40, 30);
+        assert_eq!(
+            project_onto_image(window, monitor, (100, 50)),
+            Some(PixelRect {
+                x: 80,
+                y: 0,
+                width: 20,
+                height: 20
+            })
+        );
+        // Ventana en otro monitor
+        assert_eq!(
+            project_onto_image(rect(150, 0, 40, 30), monitor, (100, 50)),
+            None
         );
     }
 
-    // Capturar el monitor primario (indice 0)
-    let monitor = &monitores[0];
-    let imagen = monitor
-        .capture_image()
-        .map_err(|e| format!("Error al capturar pantalla: {}", e))?;
+    #[test]
+    fn test_proyeccion_hidpi_y_segundo_monitor() {
+        // Monitor logico de 100x50 a la derecha del primario, capturado a 2x
+        let monitor = rect(1920, 0, 100, 50);
+        let window = rect(1930, 5, 10, 10);
+        assert_eq!(
+            project_onto_image(window, monitor, (200, 100)),
+            Some(PixelRect {
+                x: 20,
+                y: 10,
+                width: 20,
+                height: 20
+            })
+        );
+    }
 
-    // Codificar como PNG en memoria
-    let mut buffer = std::io::Cursor::new(Vec::new());
-    imagen
-        .write_to(&mut buffer, image::ImageFormat::Png)
-        .map_err(|e| format!("Error al codificar PNG: {}", e))?;
+    #[test]
+    fn test_interseccion() {
+        let monitor = rect(0, 0, 1920, 1080);
+        assert_eq!(
+            rect(1800, 1000, 400, 300).intersect(&monitor),
+            Some(rect(1800, 1000, 120, 80))
+        );
+        assert_eq!(rect(1920, 0, 100, 100).intersect(&monitor), None);
+    }
 
-    Ok(buffer.into_inner())
+    #[test]
+    fn test_monitor_con_mas_solape() {
+        let monitors = [rect(0, 0, 1920, 1080), rect(1920, 0, 2560, 1440)];
+        // Ventana a caballo, casi toda en el segundo monitor
+        assert_eq!(
+            monitor_with_most_overlap(&monitors, &rect(1900, 100, 800, 600)),
+            Some(1)
+        );
+        assert_eq!(
+            monitor_with_most_overlap(&monitors, &rect(100, 100, 800, 600)),
+            Some(0)
+        );
+        assert_eq!(
+            monitor_with_most_overlap(&monitors, &rect(-900, 0, 800, 600)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_seleccion_de_monitor() {
+        let names = ["eDP-1", "HDMI-1"];
+        assert_eq!(
+            select_monitor(&names, &MonitorSelector::Name("hdmi-1".to_string())),
+            Ok(1)
+        );
+        assert_eq!(select_monitor(&names, &MonitorSelector::Index(0)), Ok(0));
+        assert!(select_monitor(&names, &MonitorSelector::Index(2)).is_err());
+        assert!(select_monitor(&names, &MonitorSelector::Name("DP-3".to_string())).is_err());
+    }
+
+    #[test]
+    fn test_modo_de_captura_en_toml() {
+        let config: ScreenConfig = toml::from_str("").unwrap();
+        assert_eq!(config.capture, CaptureMode::FocusedMonitor);
+
+        let config: ScreenConfig = toml::from_str(
+            r#"
+            [capture]
+            mode = "monitor"
+            monitor = "HDMI-1"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.capture,
+            CaptureMode::Monitor {
+                monitor: Some(MonitorSelector::Name("HDMI-1".to_string()))
+            }
+        );
+
+        let config: ScreenConfig =
+            toml::from_str("capture = { mode = \"monitor\", monitor = 1 }").unwrap();
+        assert_eq!(
+            config.capture,
+            CaptureMode::Monitor {
+                monitor: Some(MonitorSelector::Index(1))
+            }
+        );
+
+        let config: ScreenConfig = toml::from_str(
+            "capture = { mode = \"region\", x = -10, y = 0, width = 800, height = 600 }",
+        )
+        .unwrap();
+        assert_eq!(config.capture.name(), "region");
+
+        assert!(toml::from_str::<ScreenConfig>("capture = { mode = \"ventana\" }").is_err());
+        assert!(
+            toml::from_str::<ScreenConfig>("capture = { mode = \"monitor\", pantalla = 1 }")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_modo_de_captura_desde_el_frontend() {
+        let mode: CaptureMode = serde_json::from_str(r#"{"mode":"focused_window"}"#).unwrap();
+        assert_eq!(mode, CaptureMode::FocusedWindow);
+    }
+
+    #[test]
+    fn test_codificacion_por_defecto_reduce_a_jpeg() {
+        let image = RgbaImage::from_pixel(3840, 2160, Rgba([30, 30, 30, 255]));
+        let config = EncodeConfig::default();
+        let reduced = downscale(image, &config);
+        assert_eq!((reduced.width(), reduced.height()), (1920, 1080));
+
+        let data = write_image(&reduced, &config).unwrap();
+        assert_eq!(&data[..2], &[0xFF, 0xD8]);
+        assert_eq!(
+            image::guess_format(&data).unwrap(),
+            image::ImageFormat::Jpeg
+        );
+    }
+
+    #[test]
+    fn test_codificacion_sin_reducir() {
+        let image = RgbaImage::from_pixel(800, 600, Rgba([30, 30, 30, 255]));
+        let config = EncodeConfig {
+            format: EncodeFormat::Png,
+            max_edge: 0,
+            ..Default::default()
+        };
+        let reduced = downscale(image, &config);
+        assert_eq!((reduced.width(), reduced.height()), (800, 600));
+        let data = write_image(&reduced, &config).unwrap();
+        assert_eq!(image::guess_format(&data).unwrap(), image::ImageFormat::Png);
+    }
+
+    #[test]
+    fn test_codificacion_webp_en_grises() {
+        let image = RgbaImage::from_pixel(64, 32, Rgba([200, 10, 10, 255]));
+        let config = EncodeConfig {
+            format: EncodeFormat::Webp,
+            grayscale: true,
+            ..Default::default()
+        };
+        let reduced = downscale(image, &config);
+        let data = write_image(&reduced, &config).unwrap();
+        assert_eq!(
+            image::guess_format(&data).unwrap(),
+            image::ImageFormat::WebP
+        );
+
+        let decoded = image::load_from_memory(&data).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (64, 32));
+        let pixel = decoded.to_rgb8().get_pixel(0, 0).0;
+        assert!(pixel[0] == pixel[1] && pixel[1] == pixel[2]);
+    }
+
+    #[test]
+    fn test_encoding_en_toml() {
+        let config: ScreenConfig = toml::from_str(
+            r#"
+            [encoding]
+            format = "webp"
+            grayscale = true
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.encoding.format, EncodeFormat::Webp);
+        assert_eq!(config.encoding.max_edge, 1920);
+        assert!(toml::from_str::<ScreenConfig>("encoding = { format = \"gif\" }").is_err());
+    }
+
+    #[test]
+    fn test_mask_rect() {
+        let mut image = RgbaImage::from_pixel(4, 4, Rgba([255, 255, 255, 255]));
+        mask_rect(
+            &mut image,
+            PixelRect {
+                x: 1,
+                y: 1,
+                width: 2,
+                height: 2,
+            },
+        );
+        assert_eq!(*image.get_pixel(1, 1), MASK_COLOR);
+        assert_eq!(*image.get_pixel(2, 2), MASK_COLOR);
+        assert_eq!(*image.get_pixel(0, 0), Rgba([255, 255, 255, 255]));
+        assert_eq!(*image.get_pixel(3, 1), Rgba([255, 255, 255, 255]));
+    }
+
+    fn png_entero() -> EncodeConfig {
+        EncodeConfig {
+            format: EncodeFormat::Png,
+            max_edge: 0,
+            tile_size: 0,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_captura_sintetica_tapa_el_chat() {
+        let source = SyntheticSource::new(300, 200);
+        let blocklist = WindowBlocklist::from_config(&ScreenConfig::default()).unwrap();
+        let screen = capture_screen(
+            &source,
+            &CaptureMode::Monitor { monitor: None },
+            &blocklist,
+            &png_entero(),
+            None,
+        )
+        .unwrap();
+        assert_eq!(screen.masked_windows, vec!["Slack".to_string()]);
+        assert_eq!((screen.width, screen.height), (300, 200));
+
+        let image = image::load_from_memory(&screen.data).unwrap().to_rgb8();
+        // El chat ocupa x >= 200, y < 100; el editor no se toca
+        assert_eq!(image.get_pixel(250, 50).0, [0, 0, 0]);
+        assert_eq!(image.get_pixel(250, 158).0, [250, 250, 250]);
+        assert_eq!(image.get_pixel(5, 5).0, [250, 250, 250]);
+    }
+
+    #[test]
+    fn test_captura_sintetica_de_la_ventana_enfocada() {
+        let source = SyntheticSource::new(300, 200);
+        let screen = capture_screen(
+            &source,
+            &CaptureMode::FocusedWindow,
+            &WindowBlocklist::default(),
+            &png_entero(),
+            None,
+        )
+        .unwrap();
+        // Solo el editor: los dos tercios izquierdos
+        assert_eq!((screen.width, screen.height), (200, 200));
+        assert!(screen.masked_windows.is_empty());
+    }
+
+    #[test]
+    fn test_fuente_en_toml() {
+        let config: ScreenConfig = toml::from_str("").unwrap();
+        assert_eq!(config.source, SourceConfig::Xcap);
+
+        let config: ScreenConfig = toml::from_str(
+            r#"
+            [source]
+            kind = "synthetic"
+            width = 640
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.source,
+            SourceConfig::Synthetic {
+                width: 640,
+                height: 800
+            }
+        );
+        assert!(toml::from_str::<ScreenConfig>("source = { kind = \"replay\" }").is_err());
+        assert!(toml::from_str::<ScreenConfig>("source = { kind = \"vnc\" }").is_err());
+    }
 }
@@ -1,8 +1,11 @@
 // lia-client/src-tauri/src/wakeword.rs
 // Deteccion de actividad vocal (VAD) por energia RMS.
 // Activa la grabacion cuando detecta voz y la detiene tras silencio prolongado.
-// Infraestructura para activacion hands-free (actualmente se usa el boton del HUD).
+// `UtteranceDetector` recorta una frase del stream del microfono: el boton del
+// HUD (o el modo manos libres) abre el microfono y el VAD decide cuando
+// termino de hablar el usuario.
 
+use std::collections::VecDeque;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
@@ -16,6 +19,11 @@ const ACTIVATION_MS: u64 = 200;
 /// Milisegundos de silencio para detener la grabacion.
 const SILENCE_MS: u64 = 1500;
 
+/// Milisegundos de audio previos a la activacion que se agregan a la frase.
+/// El VAD tarda `ACTIVATION_MS` en confirmar la voz; sin este margen se
+/// perderia la primera silaba.
+const PRE_ROLL_MS: u64 = 500;
+
 /// Estado del detector de voz.
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum VadState {
@@ -89,6 +97,130 @@ impl VoiceActivityDetector {
     }
 }
 
+/// Estado de una frase en curso.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UtteranceStatus {
+    /// Todavia no hablo nadie.
+    Waiting,
+    /// El usuario esta hablando (o hizo una pausa corta).
+    Speaking,
+    /// La frase termino: silencio tras la voz o duracion maxima.
+    Done,
+}
+
+/// Recorta una frase de un stream de audio mono: guarda un pre-roll mientras
+/// no hay voz y termina tras `SILENCE_MS` de silencio, nunca antes de
+/// `min_ms` ni despues de `max_ms` desde que se empezo a escuchar (o desde
+/// que empezo la voz, en modo manos libres).
+pub struct UtteranceDetector {
+    vad: VoiceActivityDetector,
+    /// Frame incompleto a la espera de mas muestras.
+    frame: Vec<f32>,
+    /// Ultimos `PRE_ROLL_MS` de audio antes de detectar voz.
+    pre_roll: VecDeque<f32>,
+    pre_roll_len: usize,
+    utterance: Vec<f32>,
+    started: bool,
+    status: UtteranceStatus,
+    /// Muestras recibidas desde el inicio, con o sin voz.
+    received: usize,
+    /// Los limites cuentan desde la voz: se puede esperar indefinidamente.
+    limits_from_voice: bool,
+    min_samples: usize,
+    max_samples: usize,
+}
+
+impl UtteranceDetector {
+    pub fn new(speaking_flag: Arc<AtomicBool>, sample_rate: u32, min_ms: u64, max_ms: u64) -> Self {
+        let samples_for = |ms: u64| (sample_rate as u64 * ms / 1000) as usize;
+        let vad = VoiceActivityDetector::new(speaking_flag, sample_rate);
+        let pre_roll_len = samples_for(PRE_ROLL_MS);
+        UtteranceDetector {
+            frame: Vec::with_capacity(vad.frame_size()),
+            vad,
+            pre_roll: VecDeque::with_capacity(pre_roll_len),
+            pre_roll_len,
+            utterance: Vec::new(),
+            started: false,
+            status: UtteranceStatus::Waiting,
+            received: 0,
+            limits_from_voice: false,
+            min_samples: samples_for(min_ms),
+            max_samples: samples_for(max_ms.max(min_ms)),
+        }
+    }
+
+    /// Detector para escucha continua: espera sin limite a que alguien hable
+    /// y las duraciones minima y maxima cuentan desde la voz.
+    pub fn hands_free(
+        speaking_flag: Arc<AtomicBool>,
+        sample_rate: u32,
+        min_ms: u64,
+        max_ms: u64,
+    ) -> Self {
+        UtteranceDetector {
+            limits_from_voice: true,
+            ..Self::new(speaking_flag, sample_rate, min_ms, max_ms)
+        }
+    }
+
+    /// Agrega muestras y retorna el estado de la frase. Una vez `Done`,
+    /// el resto del audio se ignora.
+    pub fn push(&mut self, samples: &[f32]) -> UtteranceStatus {
+        for &sample in samples {
+            if self.status == UtteranceStatus::Done {
+                break;
+            }
+            self.frame.push(sample);
+            if self.frame.len() == self.vad.frame_size() {
+                let frame = std::mem::take(&mut self.frame);
+                self.process_frame(&frame);
+                self.frame = frame;
+                self.frame.clear();
+            }
+        }
+        self.status
+    }
+
+    fn process_frame(&mut self, frame: &[f32]) {
+        if self.limits_from_voice && !self.started {
+            self.received = 0;
+        }
+        self.received += frame.len();
+        let vad_state = self.vad.process_frame(frame);
+
+        if self.started {
+            self.utterance.extend_from_slice(frame);
+        } else {
+            self.pre_roll.extend(frame);
+            let excess = self.pre_roll.len().saturating_sub(self.pre_roll_len);
+            self.pre_roll.drain(..excess);
+            if vad_state == VadState::Speaking {
+                self.started = true;
+                self.utterance.extend(self.pre_roll.drain(..));
+                if self.limits_from_voice {
+                    self.received = 0;
+                }
+            }
+        }
+
+        self.status = if self.received >= self.max_samples {
+            UtteranceStatus::Done
+        } else if !self.started {
+            UtteranceStatus::Waiting
+        } else if vad_state == VadState::Silent && self.received >= self.min_samples {
+            UtteranceStatus::Done
+        } else {
+            UtteranceStatus::Speaking
+        };
+    }
+
+    /// Audio de la frase, con el pre-roll. Vacio si nunca hubo voz.
+    pub fn into_samples(self) -> Vec<f32> {
+        self.utterance
+    }
+}
+
 /// Calcula la energia RMS (Root Mean Square) de un bloque de muestras.
 fn compute_rms(samples: &[f32]) -> f32 {
     if samples.is_empty() {
@@ -134,4 +266,88 @@ mod tests {
         assert_eq!(vad.state, VadState::Speaking);
         assert!(flag.load(Ordering::Relaxed));
     }
+
+    fn voz(ms: usize) -> Vec<f32> {
+        (0..ms * 16).map(|i| (i as f32 * 0.1).sin() * 0.5).collect()
+    }
+
+    fn silencio(ms: usize) -> Vec<f32> {
+        vec![0.0; ms * 16]
+    }
+
+    fn detector(min_ms: u64, max_ms: u64) -> UtteranceDetector {
+        UtteranceDetector::new(Arc::new(AtomicBool::new(false)), 16000, min_ms, max_ms)
+    }
+
+    #[test]
+    fn test_frase_termina_tras_silencio() {
+        let mut frase = detector(500, 15000);
+        assert_eq!(frase.push(&silencio(1000)), UtteranceStatus::Waiting);
+        // Bloques de tamano arbitrario, como los entrega el microfono
+        for bloque in voz(2000).chunks(333) {
+            assert_ne!(frase.push(bloque), UtteranceStatus::Done);
+        }
+        assert_eq!(frase.push(&silencio(1000)), UtteranceStatus::Speaking);
+        assert_eq!(frase.push(&silencio(600)), UtteranceStatus::Done);
+
+        // Pre-roll + voz + el silencio hasta el corte
+        let samples = frase.into_samples();
+        let ms = samples.len() / 16;
+        assert_eq!(ms, PRE_ROLL_MS as usize + 2000 - 200 + SILENCE_MS as usize);
+    }
+
+    #[test]
+    fn test_pre_roll_conserva_el_inicio() {
+        let mut frase = detector(0, 15000);
+        frase.push(&silencio(1000));
+        frase.push(&voz(1000));
+        frase.push(&silencio(SILENCE_MS as usize));
+
+        let samples = frase.into_samples();
+        // Los primeros 200ms de voz llegaron antes de la activacion
+        let pre_roll = PRE_ROLL_MS as usize * 16;
+        let inicio_voz = pre_roll - 200 * 16;
+        assert!(samples[..inicio_voz].iter().all(|&s| s == 0.0));
+        assert_eq!(&samples[inicio_voz..inicio_voz + 16000], &voz(1000)[..]);
+    }
+
+    #[test]
+    fn test_duracion_minima() {
+        // Una pausa antes del minimo no corta la frase
+        let mut frase = detector(6000, 15000);
+        frase.push(&voz(500));
+        assert_eq!(frase.push(&silencio(2000)), UtteranceStatus::Speaking);
+        frase.push(&voz(500));
+        assert_eq!(frase.push(&silencio(2000)), UtteranceStatus::Speaking);
+        assert_eq!(frase.push(&silencio(1000)), UtteranceStatus::Done);
+    }
+
+    #[test]
+    fn test_duracion_maxima() {
+        let mut frase = detector(500, 3000);
+        assert_eq!(frase.push(&voz(2000)), UtteranceStatus::Speaking);
+        assert_eq!(frase.push(&voz(2000)), UtteranceStatus::Done);
+        // Lo que llega despues del corte se ignora
+        let ms = frase.into_samples().len() / 16;
+        assert!(ms <= 3000, "{}ms", ms);
+
+        // Sin voz, la espera tambien termina en el maximo
+        let mut frase = detector(500, 3000);
+        assert_eq!(frase.push(&silencio(2000)), UtteranceStatus::Waiting);
+        assert_eq!(frase.push(&silencio(2000)), UtteranceStatus::Done);
+        assert!(frase.into_samples().is_empty());
+    }
+
+    #[test]
+    fn test_manos_libres_espera_sin_limite() {
+        let mut frase =
+            UtteranceDetector::hands_free(Arc::new(AtomicBool::new(false)), 16000, 500, 3000);
+        assert_eq!(frase.push(&silencio(10000)), UtteranceStatus::Waiting);
+        assert_eq!(frase.push(&voz(2000)), UtteranceStatus::Speaking);
+        // El maximo cuenta desde la activacion, no desde el inicio
+        assert_eq!(frase.push(&voz(1000)), UtteranceStatus::Speaking);
+        assert_eq!(frase.push(&voz(1000)), UtteranceStatus::Done);
+        let ms = frase.into_samples().len() / 16;
+        assert_eq!(ms, PRE_ROLL_MS as usize + 3000);
+    }
 }
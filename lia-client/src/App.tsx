@@ -10,6 +10,8 @@ import { getCurrentWindow } from '@tauri-apps/api/window';
 import StatusOrb, { type LiaState } from './components/StatusOrb';
 import StreamingText from './components/StreamingText';
 import ContextBar from './components/ContextBar';
+import SentinelBadge from './components/SentinelBadge';
+import PrivacyNotice, { type PolicyAction } from './components/PrivacyNotice';
 import './App.css';
 
 interface ContextInfo {
@@ -18,17 +20,70 @@ interface ContextInfo {
   cursorLine: number;
 }
 
+interface SentinelReport {
+  total: number;
+  allowed: number;
+  byRule: Record<string, number>;
+}
+
+interface PlaybackSettings {
+  outputDevice: string | null;
+  volume: number;
+  speed: number;
+  preservePitch: boolean;
+}
+
+const SPEEDS = [0.75, 1, 1.25, 1.5, 2];
+
+interface PrivacyPolicyInfo {
+  fileName: string;
+  action: PolicyAction;
+  reason: string;
+}
+
 function App() {
   const [state, setState] = useState<LiaState>('IDLE');
   const [streamText, setStreamText] = useState('');
   const [isProcessing, setIsProcessing] = useState(false);
+  const [handsFree, setHandsFree] = useState(false);
+  const [paused, setPaused] = useState(false);
+  const [volume, setVolume] = useState(1);
+  const [speed, setSpeed] = useState(1);
+  const [outputDevice, setOutputDevice] = useState<string | null>(null);
+  const [outputDevices, setOutputDevices] = useState<string[]>([]);
   const [context, setContext] = useState<ContextInfo>({
     fileName: '',
     language: '',
     cursorLine: 0,
   });
+  const [sentinelReport, setSentinelReport] = useState<SentinelReport>({
+    total: 0,
+    allowed: 0,
+    byRule: {},
+  });
+  const [privacy, setPrivacy] = useState<PrivacyPolicyInfo>({
+    fileName: '',
+    action: 'redact',
+    reason: '',
+  });
 
   useEffect(() => {
+    invoke<boolean>('hands_free_enabled')
+      .then(setHandsFree)
+      .catch((e) => console.error('Error leyendo manos libres:', e));
+
+    invoke<PlaybackSettings>('playback_settings')
+      .then((settings) => {
+        setVolume(settings.volume);
+        setSpeed(settings.speed);
+        setOutputDevice(settings.outputDevice);
+      })
+      .catch((e) => console.error('Error leyendo ajustes de la voz:', e));
+
+    invoke<string[]>('output_devices')
+      .then(setOutputDevices)
+      .catch((e) => console.error('Error listando salidas de audio:', e));
+
     const unlistenState = listen<string>('lia://state-change', (event) => {
       const newState = event.payload as LiaState;
       setState(newState);
@@ -41,6 +96,7 @@ function App() {
 
     const unlistenClear = listen('lia://stream-clear', () => {
       setStreamText('');
+      setPaused(false);
     });
 
     const unlistenEnd = listen('lia://stream-end', () => {
@@ -52,12 +108,22 @@ function App() {
       setContext(event.payload);
     });
 
+    const unlistenSentinel = listen<SentinelReport>('lia://sentinel-report', (event) => {
+      setSentinelReport(event.payload);
+    });
+
+    const unlistenPrivacy = listen<PrivacyPolicyInfo>('lia://privacy-policy', (event) => {
+      setPrivacy(event.payload);
+    });
+
     return () => {
       unlistenState.then((fn) => fn());
       unlistenChunk.then((fn) => fn());
       unlistenClear.then((fn) => fn());
       unlistenEnd.then((fn) => fn());
       unlistenContext.then((fn) => fn());
+      unlistenSentinel.then((fn) => fn());
+      unlistenPrivacy.then((fn) => fn());
     };
   }, []);
 
@@ -73,6 +139,65 @@ function App() {
     }
   }, [isProcessing]);
 
+  // Activar o desactivar la escucha continua
+  const handleToggleHandsFree = useCallback(async () => {
+    try {
+      setHandsFree(await invoke<boolean>('set_hands_free', { enabled: !handsFree }));
+    } catch (e) {
+      console.error('Error cambiando manos libres:', e);
+    }
+  }, [handsFree]);
+
+  // Controles de la voz de Lia
+  const handleTogglePause = useCallback(async () => {
+    try {
+      await invoke(paused ? 'resume_playback' : 'pause_playback');
+      setPaused(!paused);
+    } catch (e) {
+      console.error('Error pausando la voz:', e);
+    }
+  }, [paused]);
+
+  const handleStopPlayback = useCallback(async () => {
+    try {
+      await invoke('stop_playback');
+      setPaused(false);
+    } catch (e) {
+      console.error('Error deteniendo la voz:', e);
+    }
+  }, []);
+
+  const handleVolume = useCallback(async (value: number) => {
+    setVolume(value);
+    try {
+      await invoke('set_volume', { volume: value });
+    } catch (e) {
+      console.error('Error cambiando el volumen:', e);
+    }
+  }, []);
+
+  const handleSpeed = useCallback(async (value: number) => {
+    try {
+      setSpeed(await invoke<number>('set_speed', { speed: value }));
+    } catch (e) {
+      console.error('Error cambiando la velocidad:', e);
+    }
+  }, []);
+
+  const handleOutputDevice = useCallback(async (device: string | null) => {
+    try {
+      await invoke('set_output_device', { device });
+      setOutputDevice(device);
+    } catch (e) {
+      console.error('Error cambiando la salida de audio:', e);
+    }
+  }, []);
+
+  // La velocidad de config.toml puede no estar entre las de la lista
+  const speedOptions = SPEEDS.includes(speed)
+    ? SPEEDS
+    : SPEEDS.concat(speed).sort((a, b) => a - b);
+
   const handleClose = () => {
     getCurrentWindow().close();
   };
@@ -89,6 +214,16 @@ function App() {
       <div className="hud-main">
         <StatusOrb state={state} />
         <StreamingText text={streamText} state={state} />
+        <PrivacyNotice
+          fileName={privacy.fileName}
+          action={privacy.action}
+          reason={privacy.reason}
+        />
+        <SentinelBadge
+          total={sentinelReport.total}
+          allowed={sentinelReport.allowed}
+          byRule={sentinelReport.byRule}
+        />
 
         {/* Boton de accion */}
         <button
@@ -98,6 +233,53 @@ function App() {
         >
           {isProcessing ? 'Procesando...' : 'Preguntar a Lia'}
         </button>
+        <label className="hands-free-toggle">
+          <input type="checkbox" checked={handsFree} onChange={handleToggleHandsFree} />
+          Manos libres
+        </label>
+        <div className="playback-controls">
+          <button className="playback-button" onClick={handleTogglePause}>
+            {paused ? 'Reanudar' : 'Pausar'}
+          </button>
+          <button className="playback-button" onClick={handleStopPlayback}>
+            Detener
+          </button>
+          <input
+            className="playback-volume"
+            type="range"
+            min={0}
+            max={1}
+            step={0.05}
+            value={volume}
+            onChange={(e) => handleVolume(Number(e.target.value))}
+            aria-label="Volumen"
+          />
+          <select
+            className="playback-select"
+            value={speed}
+            onChange={(e) => handleSpeed(Number(e.target.value))}
+            aria-label="Velocidad"
+          >
+            {speedOptions.map((s) => (
+              <option key={s} value={s}>
+                {s}x
+              </option>
+            ))}
+          </select>
+        </div>
+        <select
+          className="playback-select playback-select--device"
+          value={outputDevice ?? ''}
+          onChange={(e) => handleOutputDevice(e.target.value || null)}
+          aria-label="Salida de audio"
+        >
+          <option value="">Salida del sistema</option>
+          {outputDevices.map((name) => (
+            <option key={name} value={name}>
+              {name}
+            </option>
+          ))}
+        </select>
       </div>
 
       {/* Barra de contexto */}
@@ -0,0 +1,22 @@
+// PrivacyNotice.tsx
+// Aviso de la politica de privacidad cuando el archivo activo esta bloqueado
+// (no sale codigo ni pantalla). Lo que se envia siempre pasa por Sentinel.
+
+export type PolicyAction = 'send' | 'redact' | 'block';
+
+interface PrivacyNoticeProps {
+    fileName: string;
+    action: PolicyAction;
+    reason: string;
+}
+
+export default function PrivacyNotice({ fileName, action, reason }: PrivacyNoticeProps) {
+    if (action !== 'block') return null;
+
+    return (
+        <div className="sentinel-badge" title={reason}>
+            <span className="sentinel-dot privacy-dot--block" />
+            <span className="sentinel-text">{`${fileName} no se envio (${reason})`}</span>
+        </div>
+    );
+}
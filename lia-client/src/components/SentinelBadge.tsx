@@ -0,0 +1,30 @@
+// SentinelBadge.tsx
+// Aviso compacto de lo que Sentinel redacto antes de enviar la peticion,
+// y de lo que una allowlist dejo pasar.
+
+interface SentinelBadgeProps {
+    total: number;
+    allowed: number;
+    byRule: Record<string, number>;
+}
+
+export default function SentinelBadge({ total, allowed, byRule }: SentinelBadgeProps) {
+    if (total === 0 && allowed === 0) return null;
+
+    const rules = Object.keys(byRule).join(', ');
+    const parts: string[] = [];
+    if (total > 0) {
+        const label = total === 1 ? 'secreto redactado' : 'secretos redactados';
+        parts.push(`${total} ${label} (${rules})`);
+    }
+    if (allowed > 0) {
+        parts.push(`${allowed} ${allowed === 1 ? 'permitido' : 'permitidos'} por allowlist`);
+    }
+
+    return (
+        <div className="sentinel-badge" title={rules}>
+            <span className="sentinel-dot" />
+            <span className="sentinel-text">{parts.join(' · ')}</span>
+        </div>
+    );
+}
@@ -15,6 +15,8 @@ LIA_CLIENT_TOKEN = os.getenv("LIA_CLIENT_TOKEN", "lia-dev-token-2024")
 # Cache LRU: tiempo de vida en segundos (15 minutos por defecto)
 CACHE_TTL_SECONDS = int(os.getenv("CACHE_TTL_SECONDS", "900"))
 CACHE_MAX_ENTRIES = int(os.getenv("CACHE_MAX_ENTRIES", "50"))
+# Cache de mosaicos de pantalla: una captura 1920x1080 son 40 mosaicos de 256 px
+TILE_CACHE_MAX_ENTRIES = int(os.getenv("TILE_CACHE_MAX_ENTRIES", "400"))
 
 # Servidor
 HOST = os.getenv("LIA_HOST", "0.0.0.0")
@@ -23,8 +23,11 @@ REGLAS CRITICAS:
 1. Tu fuente de verdad para el codigo es el TEXTO provisto, NO la imagen. 
    La imagen es solo para entender el error visual o la GUI del navegador.
    Si hay discrepancia entre la imagen y el texto del codigo, confia en el texto.
-2. Nunca reveles secretos redactados. Si ves <SECRET_REDACTED> en el codigo, 
-   ignoralo y no intentes adivinar el valor original.
+2. Nunca reveles secretos redactados. Los secretos llegan como placeholders
+   tipados y numerados (<AWS_KEY_1>, <EMAIL_2>) o como <SECRET_REDACTED>.
+   No intentes adivinar el valor original. Si tu codigo sugerido usa uno de
+   esos valores, escribe el placeholder exactamente igual: el cliente lo
+   restaura localmente.
 3. Se concisa pero precisa. El usuario esta programando y necesita respuestas 
    accionables, no ensayos.
 4. Si sugieres codigo, usa el lenguaje de programacion que el usuario esta usando.
@@ -40,34 +43,65 @@ def _create_client() -> genai.Client:
     )
 
 
+# Como describir la captura segun el modo del cliente (VisionPayload.mode)
+CAPTURE_DESCRIPTIONS = {
+    "monitor": "de un monitor",
+    "focused_monitor": "del monitor donde trabaja",
+    "focused_window": "de la ventana activa",
+    "region": "de una region de la pantalla",
+}
+
+
 def build_prompt_parts(
     code: str | None,
     language: str | None,
     image_bytes: bytes | None,
     audio_transcript: str | None,
+    code_blocked: bool = False,
+    masked_windows: int = 0,
+    capture_mode: str | None = None,
+    image_mime_type: str = "image/png",
+    screen_text: str | None = None,
 ) -> list[Part]:
     """Construye las partes del prompt multimodal para Gemini.
 
-    Combina texto del codigo, imagen de la pantalla y transcripcion
-    de audio en un formato que Gemini pueda procesar.
+    Combina texto del codigo, imagen de la pantalla (o su texto, si el
+    cliente usa OCR local) y transcripcion de audio en un formato que
+    Gemini pueda procesar.
     """
     parts: list[Part] = []
 
     # Contexto de codigo
-    if code:
+    if code_blocked:
+        parts.append(Part.from_text(
+            text="El archivo activo esta protegido por la politica de privacidad del usuario: "
+                 "su codigo y la pantalla no se enviaron. No pidas que lo pegue."
+        ))
+    elif code:
         lang_label = language or "desconocido"
         parts.append(Part.from_text(
             text=f"CODIGO ACTUAL DEL USUARIO (lenguaje: {lang_label}):\n```{lang_label}\n{code}\n```"
         ))
 
     # Captura de pantalla
+    capture_label = CAPTURE_DESCRIPTIONS.get(capture_mode, "de la pantalla")
     if image_bytes:
         parts.append(Part.from_bytes(
             data=image_bytes,
-            mime_type="image/png",
+            mime_type=image_mime_type,
         ))
         parts.append(Part.from_text(
-            text="La imagen anterior es una captura de la pantalla del usuario."
+            text=f"La imagen anterior es una captura {capture_label} del usuario."
+        ))
+    elif screen_text:
+        parts.append(Part.from_text(
+            text=f"TEXTO VISIBLE EN UNA CAPTURA {capture_label.upper()} DEL USUARIO "
+                 f"(extraido por OCR, puede tener errores de lectura):\n{screen_text}"
+        ))
+    if masked_windows and (image_bytes or screen_text):
+        parts.append(Part.from_text(
+            text=f"{masked_windows} ventana(s) de la captura se taparon por privacidad "
+                 "(gestores de contrasenas, chats o terminales). No intentes adivinar su contenido."
         ))
 
     # Transcripcion de voz (lo que el usuario dijo)
@@ -89,6 +123,11 @@ async def stream_response(
     language: str | None = None,
     image_bytes: bytes | None = None,
     audio_transcript: str | None = None,
+    code_blocked: bool = False,
+    masked_windows: int = 0,
+    capture_mode: str | None = None,
+    image_mime_type: str = "image/png",
+    screen_text: str | None = None,
 ) -> AsyncGenerator[str, None]:
     """Llama a Gemini y hace yield de chunks de texto a medida que llegan.
 
@@ -96,7 +135,17 @@ async def stream_response(
     al cliente Rust en tiempo real via WebSocket (Contrato C).
     """
     client = _create_client()
-    parts = build_prompt_parts(code, language, image_bytes, audio_transcript)
+    parts = build_prompt_parts(
+        code,
+        language,
+        image_bytes,
+        audio_transcript,
+        code_blocked,
+        masked_windows,
+        capture_mode,
+        image_mime_type,
+        screen_text,
+    )
 
     config = GenerateContentConfig(
         system_instruction=SYSTEM_PROMPT,
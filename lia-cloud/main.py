@@ -10,9 +10,10 @@ import logging
 import uvicorn
 from fastapi import FastAPI, WebSocket, WebSocketDisconnect, Query
 
-from config import HOST, PORT, LIA_CLIENT_TOKEN
+from config import HOST, PORT, LIA_CLIENT_TOKEN, TILE_CACHE_MAX_ENTRIES
 from cache import LRUCache
 from inference import stream_response
+from tiles import assemble_frame
 from stt import transcribe
 from tts import synthesize
 
@@ -25,12 +26,14 @@ logger = logging.getLogger("lia.server")
 
 app = FastAPI(title="Lia Cloud", version="0.1.0")
 cache = LRUCache()
+# Los mosaicos van aparte: una captura son decenas y desplazarian al codigo
+tile_cache = LRUCache(max_entries=TILE_CACHE_MAX_ENTRIES)
 
 
 @app.get("/health")
 async def health_check():
     """Endpoint para verificar que el servidor esta arriba."""
-    return {"status": "ok", "cache_size": cache.size}
+    return {"status": "ok", "cache_size": cache.size, "tile_cache_size": tile_cache.size}
 
 
 @app.websocket("/ws/lia")
@@ -64,8 +67,15 @@ async def websocket_endpoint(
 
             request_id = request_data.get("request_id", "unknown")
             payload = request_data.get("payload", {})
+            metadata = request_data.get("metadata", {})
 
-            logger.info("Procesando peticion %s", request_id)
+            redacted_total = metadata.get("sentinel", {}).get("redacted_total", 0)
+            code_policy = metadata.get("code_policy", "redact")
+            masked_windows = metadata.get("vision", {}).get("masked_windows", 0)
+            logger.info(
+                "Procesando peticion %s (politica=%s, %d secretos redactados)",
+                request_id, code_policy, redacted_total,
+            )
 
             # 3. Resolver cache para codigo
             code_data = payload.get("code", {})
@@ -73,7 +83,10 @@ async def websocket_endpoint(
             code_content = code_data.get("content")
             code_language = code_data.get("language", "")
 
-            if code_content is not None:
+            if code_policy == "block":
+                # Archivo bloqueado por la politica de privacidad: no hay codigo
+                code_content = None
+            elif code_content is not None:
                 # Codigo nuevo: guardar en cache
                 cache.put(code_hash, code_content)
             else:
@@ -86,11 +99,25 @@ async def websocket_endpoint(
             vision_data = payload.get("vision", {})
             image_hash = vision_data.get("hash", "")
             image_b64 = vision_data.get("data_b64")
+            capture_mode = vision_data.get("mode")
+            # Clientes antiguos no envian mime_type: solo sabian mandar PNG
+            image_mime_type = vision_data.get("mime_type") or "image/png"
             image_bytes = None
-
-            if image_b64 is not None:
+            # Con OCR local el cliente envia el texto de la pantalla, nunca la imagen
+            screen_text = vision_data.get("text")
+
+            if vision_data.get("kind") == "tiles":
+                # Solo llegan los mosaicos nuevos; el frame se rearma con el cache
+                image_bytes = assemble_frame(vision_data, tile_cache)
+                if image_bytes is None:
+                    logger.warning("No se pudo rearmar la captura %s", image_hash[:12])
+            elif screen_text is not None:
+                cache.put(image_hash, screen_text)
+            elif image_b64 is not None:
                 image_bytes = base64.b64decode(image_b64)
                 cache.put(image_hash, image_bytes)
+            elif image_mime_type == "text/plain":
+                screen_text = cache.get(image_hash)
             else:
                 image_bytes = cache.get(image_hash)
 
@@ -114,7 +141,12 @@ async def websocket_endpoint(
                     code=code_content,
                     language=code_language,
                     image_bytes=image_bytes,
+                    image_mime_type=image_mime_type,
+                    screen_text=screen_text,
                     audio_transcript=audio_transcript,
+                    code_blocked=code_policy == "block",
+                    masked_windows=masked_windows,
+                    capture_mode=capture_mode,
                 ):
                     # Enviar chunk de texto en tiempo real
                     await websocket.send_json({
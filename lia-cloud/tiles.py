@@ -0,0 +1,57 @@
+# lia-cloud/tiles.py
+# Reensamblado de capturas enviadas en mosaicos (VisionPayload kind="tiles").
+# El cliente solo manda los mosaicos que cambiaron; el resto sale del cache.
+# Todo ocurre en memoria: nunca se escriben imagenes a disco.
+
+import base64
+import io
+import logging
+
+from PIL import Image
+
+from cache import LRUCache
+
+logger = logging.getLogger("lia.tiles")
+
+# mime_type del Contrato B -> formato de Pillow
+PIL_FORMATS = {
+    "image/png": "PNG",
+    "image/jpeg": "JPEG",
+    "image/webp": "WEBP",
+}
+
+
+def assemble_frame(vision_data: dict, tile_cache: LRUCache) -> bytes | None:
+    """Guarda los mosaicos nuevos y rearma el frame completo.
+
+    Retorna la imagen codificada con el mime_type del cliente, o None si
+    algun mosaico del layout ya no esta en cache.
+    """
+    for tile_hash, tile_b64 in vision_data.get("tiles", {}).items():
+        tile_cache.put(tile_hash, base64.b64decode(tile_b64))
+
+    width = vision_data.get("width", 0)
+    height = vision_data.get("height", 0)
+    tile_size = vision_data.get("tile_size", 0)
+    columns = vision_data.get("columns", 0)
+    layout = vision_data.get("layout", [])
+    if not layout or not columns or not tile_size:
+        return None
+
+    frame = Image.new("RGB", (width, height))
+    for index, tile_hash in enumerate(layout):
+        tile_bytes = tile_cache.get(tile_hash)
+        if tile_bytes is None:
+            logger.warning("Mosaico %s no esta en cache", tile_hash[:12])
+            return None
+        row, column = divmod(index, columns)
+        with Image.open(io.BytesIO(tile_bytes)) as tile:
+            frame.paste(tile.convert("RGB"), (column * tile_size, row * tile_size))
+
+    pil_format = PIL_FORMATS.get(vision_data.get("mime_type"), "PNG")
+    buffer = io.BytesIO()
+    if pil_format == "JPEG":
+        frame.save(buffer, format=pil_format, quality=90)
+    else:
+        frame.save(buffer, format=pil_format)
+    return buffer.getvalue()